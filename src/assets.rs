@@ -1,19 +1,23 @@
 use std::collections::HashMap;
 use std::hash::Hash;
 
+use mq::color::Color;
 use mq::file::load_file;
 use mq::text;
 use mq::text::Font;
 use mq::texture::{load_texture, Texture2D};
 use once_cell::sync::OnceCell;
-use serde::Deserialize;
 use serde::de::DeserializeOwned;
+use serde::Deserialize;
 
-use crate::AResult;
 use crate::error::AError;
+use crate::AResult;
 
 static INSTANCE: OnceCell<Assets> = OnceCell::new();
 
+/// The theme of the menus that don't belong to any faction.
+const DEFAULT_THEME: &str = "default";
+
 pub async fn load() -> AResult {
     assert!(INSTANCE.get().is_none());
     let assets = Assets::load().await?;
@@ -36,24 +40,37 @@ async fn deserialize_from_file<D: DeserializeOwned>(path: &str) -> AResult<D> {
     ron::de::from_str(&s).map_err(|e| AError::from_ron_de_error(e, path.into()))
 }
 
-async fn load_map<Key: Hash + Eq + Copy>(
+async fn load_map<Key: Hash + Eq + Clone>(
     table: &[(Key, &str)],
     expand_path: fn(&str) -> String,
 ) -> AResult<HashMap<Key, Texture2D>> {
     let mut map = HashMap::new();
-    for &(key, path) in table {
-        map.insert(key, load_texture(&expand_path(path)).await?);
+    for (key, path) in table {
+        map.insert(key.clone(), load_texture(&expand_path(path)).await?);
     }
     Ok(map)
 }
 
+/// Describes how an object type is drawn. Loaded from `sprites.ron`.
 #[derive(Debug, Clone, Deserialize)]
 pub struct SpriteInfo {
+    /// Frame name to image path (relative to `sprites/`).
+    /// The default frame has an empty name and is required.
     pub paths: HashMap<String, String>,
+
+    /// Horizontal shift of the sprite's anchor point from its bottom center
+    /// (as a fraction of the sprite's width).
     pub offset_x: f32,
+
+    /// Vertical shift of the sprite's anchor point from its bottom center
+    /// (as a fraction of the sprite's height).
     pub offset_y: f32,
+
+    /// Shadow's width relative to the tile size.
     pub shadow_size_coefficient: f32,
 
+    /// Used to order objects standing on the same tile.
+    /// Should be in the `-0.5..0.5` range.
     #[serde(default = "default_sub_tile_z")]
     pub sub_tile_z: f32,
 }
@@ -62,31 +79,238 @@ fn default_sub_tile_z() -> f32 {
     0.0
 }
 
-type SpritesInfo = HashMap<String, SpriteInfo>;
+pub type SpritesInfo = HashMap<String, SpriteInfo>;
+
+/// Object type to its frames.
+pub type SpriteFrames = HashMap<String, HashMap<String, Texture2D>>;
+
+async fn load_sprite_frames(sprites_info: &SpritesInfo) -> AResult<SpriteFrames> {
+    let mut sprite_frames = HashMap::new();
+    for (obj_type, SpriteInfo { paths, .. }) in sprites_info {
+        let table: Vec<(String, &str)> = paths
+            .iter()
+            .map(|(frame_name, path)| (frame_name.clone(), path.as_str()))
+            .collect();
+        let frames = load_map(&table, |path| format!("sprites/{}", path)).await?;
+        sprite_frames.insert(obj_type.clone(), frames);
+    }
+    Ok(sprite_frames)
+}
+
+/// Loads the font only once for all its users.
+async fn load_font(fonts: &mut HashMap<String, Font>, path: &str) -> AResult<Font> {
+    if let Some(&font) = fonts.get(path) {
+        return Ok(font);
+    }
+    let font = text::load_ttf_font(path).await?;
+    fonts.insert(path.into(), font);
+    Ok(font)
+}
+
+/// `(r, g, b, a)`
+type ColorInfo = (f32, f32, f32, f32);
+
+fn color((r, g, b, a): ColorInfo) -> Color {
+    Color::new(r, g, b, a)
+}
+
+/// See `ui::Palette`.
+#[derive(Debug, Clone, Deserialize)]
+struct PaletteInfo {
+    text: ColorInfo,
+    text_inactive: ColorInfo,
+    bg: ColorInfo,
+    bg_highlighted: ColorInfo,
+    border: ColorInfo,
+    focus: ColorInfo,
+    error: ColorInfo,
+    selection: ColorInfo,
+}
+
+impl PaletteInfo {
+    fn to_palette(&self) -> ui::Palette {
+        ui::Palette {
+            text: color(self.text),
+            text_inactive: color(self.text_inactive),
+            bg: color(self.bg),
+            bg_highlighted: color(self.bg_highlighted),
+            border: color(self.border),
+            focus: color(self.focus),
+            error: color(self.error),
+            selection: color(self.selection),
+        }
+    }
+}
+
+/// See `ui::NineSlice`.
+#[derive(Debug, Clone, Deserialize)]
+struct NineSliceInfo {
+    path: String,
+    margin: f32,
+    corner: f32,
+}
+
+async fn load_nine_slice(info: &NineSliceInfo) -> AResult<ui::NineSlice> {
+    Ok(ui::NineSlice {
+        texture: load_texture(&info.path).await?,
+        margin: info.margin,
+        corner: info.corner,
+    })
+}
+
+async fn load_nine_slice_opt(info: &Option<NineSliceInfo>) -> AResult<Option<ui::NineSlice>> {
+    match info {
+        Some(info) => Ok(Some(load_nine_slice(info).await?)),
+        None => Ok(None),
+    }
+}
+
+/// See `ui::ButtonTextures`.
+#[derive(Debug, Clone, Deserialize)]
+struct ButtonTexturesInfo {
+    normal: NineSliceInfo,
+    hovered: Option<NineSliceInfo>,
+    pressed: Option<NineSliceInfo>,
+    disabled: Option<NineSliceInfo>,
+}
+
+async fn load_button_textures(info: &ButtonTexturesInfo) -> AResult<ui::ButtonTextures> {
+    Ok(ui::ButtonTextures {
+        normal: load_nine_slice(&info.normal).await?,
+        hovered: load_nine_slice_opt(&info.hovered).await?,
+        pressed: load_nine_slice_opt(&info.pressed).await?,
+        disabled: load_nine_slice_opt(&info.disabled).await?,
+    })
+}
+
+/// Describes a look of the menus, see `ui::Theme`. Loaded from `themes.ron`.
+#[derive(Debug, Clone, Deserialize)]
+struct ThemeInfo {
+    colors: PaletteInfo,
+
+    /// Thickness of the borders as a part of the widgets' height,
+    /// overrides the widgets' own one.
+    border_k: f32,
+
+    /// Distance between the screen's edges and the widgets.
+    padding: f32,
+
+    /// Font path.
+    font: String,
+
+    /// Bold font path.
+    bold_font: Option<String>,
+
+    panel: Option<NineSliceInfo>,
+    button: Option<ButtonTexturesInfo>,
+}
+
+async fn load_themes(
+    themes_info: &HashMap<String, ThemeInfo>,
+    fonts: &mut HashMap<String, Font>,
+) -> AResult<HashMap<String, ui::Theme>> {
+    let mut themes = HashMap::new();
+    for (name, info) in themes_info {
+        let bold_font = match info.bold_font {
+            Some(ref path) => Some(load_font(fonts, path).await?),
+            None => None,
+        };
+        let button = match info.button {
+            Some(ref button) => Some(load_button_textures(button).await?),
+            None => None,
+        };
+        let theme = ui::Theme {
+            colors: info.colors.to_palette(),
+            border_k: Some(info.border_k),
+            padding: info.padding,
+            font: load_font(fonts, &info.font).await?,
+            bold_font,
+            panel: load_nine_slice_opt(&info.panel).await?,
+            button,
+        };
+        theme.check()?;
+        themes.insert(name.clone(), theme);
+    }
+    if !themes.contains_key(DEFAULT_THEME) {
+        return Err(AError::NoDefaultTheme);
+    }
+    Ok(themes)
+}
 
 #[derive(Debug)]
 pub struct Assets {
     pub font: Font,
+    pub textures: MapObjectTextures,
+    pub sprites_info: SpritesInfo,
+    pub sprite_frames: SpriteFrames,
+
+    /// The menus' looks, one per faction.
+    pub themes: HashMap<String, ui::Theme>,
 }
 
 impl Assets {
     pub async fn load() -> AResult<Self> {
+        let sprites_info: SpritesInfo = deserialize_from_file("sprites.ron").await?;
+        let sprite_frames = load_sprite_frames(&sprites_info).await?;
+        let themes_info: HashMap<String, ThemeInfo> = deserialize_from_file("themes.ron").await?;
+        let mut fonts = HashMap::new();
         Ok(Self {
-            font: text::load_ttf_font("OpenSans-Regular.ttf").await?,
+            font: load_font(&mut fonts, "OpenSans-Regular.ttf").await?,
+            textures: MapObjectTextures::load().await?,
+            sprites_info,
+            sprite_frames,
+            themes: load_themes(&themes_info, &mut fonts).await?,
         })
     }
+
+    pub fn default_theme(&self) -> &ui::Theme {
+        &self.themes[DEFAULT_THEME]
+    }
+
+    /// The faction's theme or the default one if the faction has none.
+    pub fn theme(&self, faction: &str) -> &ui::Theme {
+        self.themes
+            .get(faction)
+            .unwrap_or_else(|| self.default_theme())
+    }
 }
 
+const BLOCK_FRAMES: &[(&str, &str)] = &[
+    ("", "plain.png"),
+    ("grass", "grass.png"),
+    ("fire", "fire.png"),
+    ("ice", "ice.png"),
+    ("water", "water.png"),
+    ("swamp", "swamp.png"),
+];
+
 #[derive(Debug)]
 pub struct MapObjectTextures {
-    pub tile: Texture2D,
+    /// Flat terrain atlas, one tile per `core::map::Terrain` in its order.
+    pub terrain: Texture2D,
+
+    pub shadow: Texture2D,
+
+    /// Isometric terrain block frames, one block per elevation level.
+    /// The default frame is the plain terrain, the others are named
+    /// after the terrain types.
+    pub blocks: HashMap<String, Texture2D>,
+
+    /// Tile highlights atlas, see `screen::battle::view::overlay`.
+    pub overlay: Texture2D,
 }
 
 impl MapObjectTextures {
     async fn load() -> AResult<Self> {
+        let block_frames: Vec<(String, &str)> = BLOCK_FRAMES
+            .iter()
+            .map(|&(frame_name, path)| (frame_name.into(), path))
+            .collect();
         Ok(Self {
-            tile: load_texture("textures/tile.png").await?,
+            terrain: load_texture("textures/terrain.png").await?,
+            shadow: load_texture("textures/shadow.png").await?,
+            blocks: load_map(&block_frames, |path| format!("textures/blocks/{}", path)).await?,
+            overlay: load_texture("textures/overlay.png").await?,
         })
     }
 }
-
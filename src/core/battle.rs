@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::core::map::{Dir, Map, Pos};
+
+mod event;
+mod execute;
+mod forced;
+mod movement;
+mod terrain;
+mod vision;
+
+pub use self::{
+    event::Event,
+    execute::{attack, can_attack, can_heal, heal, Side},
+    forced::Displacement,
+    movement::{can_move, move_to, turn, Paths},
+    terrain::{can_use_skill, end_turn, use_skill, Skill},
+    vision::{TileVisibility, Vision},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjId(i32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlayerId(pub i32);
+
+/// Hit points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hp {
+    pub current: i32,
+    pub max: i32,
+}
+
+impl Hp {
+    pub fn new(max: i32) -> Self {
+        Self { current: max, max }
+    }
+}
+
+/// Anything that occupies a tile: units and props like boulders
+/// or destructible crates.
+#[derive(Debug, Clone)]
+pub struct Object {
+    /// Name of the object's type in `sprites.ron`.
+    pub obj_type: String,
+
+    pub pos: Pos,
+
+    /// Set by the movement, the attacks and the turns.
+    pub facing: Dir,
+
+    /// Props don't belong to any player.
+    pub player: Option<PlayerId>,
+
+    /// Objects without hit points can't be damaged, the props
+    /// with them can be destroyed.
+    pub hp: Option<Hp>,
+}
+
+impl Object {
+    pub fn is_unit(&self) -> bool {
+        self.player.is_some()
+    }
+
+    // TODO: Move to the class definitions.
+    pub fn can_heal(&self) -> bool {
+        self.obj_type == "monk"
+    }
+
+    // TODO: Move to the class definitions.
+    pub fn move_points(&self) -> i16 {
+        match self.obj_type.as_str() {
+            "lancer" => 4,
+            _ if self.is_unit() => 3,
+            _ => 0,
+        }
+    }
+
+    // TODO: Move to the class definitions.
+    pub fn attack_range(&self) -> i16 {
+        match self.obj_type.as_str() {
+            "sniper" => 4,
+            "lancer" => 2,
+            "summoner" => 3,
+            _ => 1,
+        }
+    }
+
+    // TODO: Move to the class definitions.
+    pub fn sight_range(&self) -> i16 {
+        match self.obj_type.as_str() {
+            "sniper" => 5,
+            _ if self.is_unit() => 4,
+            _ => 0,
+        }
+    }
+
+    // TODO: Move to the class definitions.
+    pub fn attack_displacement(&self) -> Option<Displacement> {
+        match self.obj_type.as_str() {
+            "lancer" => Some(Displacement::Push(1)),
+            "golem" => Some(Displacement::Push(2)),
+            "summoner" => Some(Displacement::Pull(2)),
+            _ => None,
+        }
+    }
+
+    // TODO: Move to the class definitions.
+    pub fn skill(&self) -> Option<Skill> {
+        match self.obj_type.as_str() {
+            "sniper" => Some(Skill::Ignite),
+            "monk" => Some(Skill::Frost),
+            _ => None,
+        }
+    }
+
+    // TODO: Move to the class definitions.
+    /// Sides covered by a shield, the attacks from them may be blocked.
+    pub fn shield_sides(&self) -> &'static [Side] {
+        match self.obj_type.as_str() {
+            "knight" => &[Side::Front],
+            "phalanx" => &[Side::Front, Side::Flank],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    map: Map,
+    objects: BTreeMap<ObjId, Object>,
+    next_obj_id: ObjId,
+
+    /// Every player's fog of war, created with the player's first unit.
+    visions: HashMap<PlayerId, Vision>,
+}
+
+impl State {
+    pub fn new(map: Map) -> Self {
+        Self {
+            map,
+            objects: BTreeMap::new(),
+            next_obj_id: ObjId(0),
+            visions: HashMap::new(),
+        }
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn add_object(&mut self, object: Object) -> ObjId {
+        let id = self.next_obj_id;
+        self.next_obj_id.0 += 1;
+        if let Some(player) = object.player {
+            let map = &self.map;
+            self.visions
+                .entry(player)
+                .or_insert_with(|| Vision::new(map));
+        }
+        self.objects.insert(id, object);
+        // Nothing is shown yet, so there's no need in the events.
+        self.update_visions();
+        id
+    }
+
+    pub fn object(&self, id: ObjId) -> &Object {
+        &self.objects[&id]
+    }
+
+    pub fn object_at(&self, pos: Pos) -> Option<ObjId> {
+        self.objects()
+            .find(|(_, object)| object.pos == pos)
+            .map(|(id, _)| id)
+    }
+
+    pub fn vision(&self, player: PlayerId) -> &Vision {
+        &self.visions[&player]
+    }
+
+    /// Checks if the player knows about the object: the player's own
+    /// units and the props are always known, the enemies only in sight.
+    pub fn is_known(&self, player: PlayerId, id: ObjId) -> bool {
+        match self.object(id).player {
+            Some(owner) if owner != player => self.vision(player).is_enemy_visible(id),
+            _ => true,
+        }
+    }
+
+    /// Objects the player knows about, everything the player's side
+    /// (the view or the AI) should decide by.
+    pub fn known_objects(&self, player: PlayerId) -> impl Iterator<Item = (ObjId, &Object)> {
+        self.objects()
+            .filter(move |&(id, _)| self.is_known(player, id))
+    }
+
+    pub fn known_object_at(&self, player: PlayerId, pos: Pos) -> Option<ObjId> {
+        self.known_objects(player)
+            .find(|(_, object)| object.pos == pos)
+            .map(|(id, _)| id)
+    }
+
+    /// Shows the enemy unit to the player until the next vision update,
+    /// used when a move runs into the unit.
+    pub(super) fn reveal(&mut self, player: PlayerId, id: ObjId) -> Event {
+        let vision = self.visions.get_mut(&player).expect("no such vision");
+        vision.reveal(id);
+        Event::Revealed { player, id }
+    }
+
+    /// Recomputes all players' visions, see [vision::update].
+    pub(super) fn update_visions(&mut self) -> Vec<Event> {
+        let mut players: Vec<PlayerId> = self.visions.keys().copied().collect();
+        players.sort_by_key(|player| player.0);
+        let mut events = Vec::new();
+        for player in players {
+            let mut vision = self.visions.remove(&player).expect("no such vision");
+            events.extend(vision::update(self, player, &mut vision));
+            self.visions.insert(player, vision);
+        }
+        events
+    }
+
+    pub fn apply(&mut self, event: &Event) {
+        match *event {
+            Event::Moved { id, ref path } => {
+                let object = self.objects.get_mut(&id).expect("no such object");
+                let last = *path.last().expect("empty path");
+                let prev = path.iter().rev().nth(1).copied().unwrap_or(object.pos);
+                object.facing = Dir::towards(prev, last).expect("bad path");
+                object.pos = last;
+            }
+            Event::Pushed { id, ref path } => {
+                let object = self.objects.get_mut(&id).expect("no such object");
+                object.pos = *path.last().expect("empty path");
+            }
+            Event::Turned { id, facing } => {
+                let object = self.objects.get_mut(&id).expect("no such object");
+                object.facing = facing;
+            }
+            Event::Damaged { id, amount, .. } => {
+                let hp = self.hp_mut(id);
+                hp.current = (hp.current - amount).max(0);
+            }
+            Event::Healed { id, amount } => {
+                let hp = self.hp_mut(id);
+                hp.current = (hp.current + amount).min(hp.max);
+            }
+            Event::Missed { .. }
+            | Event::Blocked { .. }
+            | Event::Fell { .. }
+            | Event::Bumped { .. } => {}
+            Event::TerrainChanged { pos, terrain } => self.map.set_terrain_at(pos, terrain),
+            // The visions are updated right after the other events.
+            Event::Revealed { .. } | Event::Hidden { .. } => {}
+            Event::Died { id } => {
+                self.objects.remove(&id);
+            }
+        }
+    }
+
+    fn hp_mut(&mut self, id: ObjId) -> &mut Hp {
+        let object = self.objects.get_mut(&id).expect("no such object");
+        object.hp.as_mut().expect("object has no hit points")
+    }
+
+    pub fn objects(&self) -> impl Iterator<Item = (ObjId, &Object)> {
+        self.objects.iter().map(|(&id, object)| (id, object))
+    }
+}
@@ -0,0 +1,77 @@
+use crate::core::{
+    battle::{ObjId, PlayerId},
+    map::{Dir, Pos, Terrain},
+};
+
+/// Describes a change of the battle state, shown by the view.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The path starts with the tile next to the object's old position.
+    /// The object ends up facing the direction of the last step.
+    Moved {
+        id: ObjId,
+        path: Vec<Pos>,
+    },
+    Turned {
+        id: ObjId,
+        facing: Dir,
+    },
+
+    /// Forced movement along a straight line, the facing doesn't change.
+    /// The path starts with the tile next to the object's old position.
+    Pushed {
+        id: ObjId,
+        path: Vec<Pos>,
+    },
+
+    /// The pushed object has fallen down this many levels.
+    Fell {
+        id: ObjId,
+        levels: i16,
+    },
+
+    /// The pushed object has hit another object or a wall
+    /// (a map's edge or a high tile) if `obstacle` is `None`.
+    Bumped {
+        id: ObjId,
+        dir: Dir,
+        obstacle: Option<ObjId>,
+    },
+    Damaged {
+        id: ObjId,
+        amount: i32,
+        is_critical: bool,
+    },
+    Healed {
+        id: ObjId,
+        amount: i32,
+    },
+    Missed {
+        id: ObjId,
+    },
+
+    /// The attack has hit the target's shield.
+    Blocked {
+        id: ObjId,
+    },
+    Died {
+        id: ObjId,
+    },
+
+    TerrainChanged {
+        pos: Pos,
+        terrain: Terrain,
+    },
+
+    /// The enemy unit has entered the player's sight.
+    Revealed {
+        player: PlayerId,
+        id: ObjId,
+    },
+
+    /// The enemy unit has left the player's sight.
+    Hidden {
+        player: PlayerId,
+        id: ObjId,
+    },
+}
@@ -0,0 +1,156 @@
+use mq::rand::gen_range;
+
+use crate::core::{
+    battle::{forced, Event, ObjId, State},
+    map::{Dir, Pos},
+};
+
+const HIT_CHANCE: f32 = 0.8;
+const CRITICAL_CHANCE: f32 = 0.15;
+const CRITICAL_MULTIPLIER: i32 = 2;
+const MIN_DAMAGE: i32 = 2;
+const MAX_DAMAGE: i32 = 4;
+const HEAL_AMOUNT: i32 = 3;
+const FLANK_ATTACK_BONUS: i32 = 1;
+const BACK_ATTACK_BONUS: i32 = 2;
+const SHIELD_BLOCK_CHANCE: f32 = 0.5;
+
+/// The side of the target an attack comes from, relative to its facing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Front,
+    Flank,
+    Back,
+}
+
+impl Side {
+    fn bonus(self) -> i32 {
+        match self {
+            Side::Front => 0,
+            Side::Flank => FLANK_ATTACK_BONUS,
+            Side::Back => BACK_ATTACK_BONUS,
+        }
+    }
+}
+
+/// Diagonal attacks count as coming from the front or the back.
+fn attack_side(attacker: Pos, target: Pos, target_facing: Dir) -> Side {
+    let (fx, fy) = target_facing.delta();
+    let (dx, dy) = (attacker.x - target.x, attacker.y - target.y);
+    let forward = fx * dx + fy * dy;
+    let sideways = fx * dy - fy * dx;
+    if forward.abs() < sideways.abs() {
+        Side::Flank
+    } else if forward > 0 {
+        Side::Front
+    } else {
+        Side::Back
+    }
+}
+
+pub fn can_attack(state: &State, attacker_id: ObjId, target_id: ObjId) -> bool {
+    let attacker = state.object(attacker_id);
+    let target = state.object(target_id);
+    let player = match attacker.player {
+        Some(player) => player,
+        None => return false,
+    };
+    state.is_known(player, target_id)
+        && target.hp.is_some()
+        && attacker.player != target.player
+        && attacker.pos.distance(target.pos) <= attacker.attack_range()
+}
+
+pub fn can_heal(state: &State, healer_id: ObjId, target_id: ObjId) -> bool {
+    let healer = state.object(healer_id);
+    let target = state.object(target_id);
+    healer.can_heal()
+        && target.is_unit()
+        && target.hp.is_some_and(|hp| hp.current < hp.max)
+        && healer.player == target.player
+        && healer.pos.distance(target.pos) <= healer.attack_range()
+}
+
+/// Executes the attack and applies the resulting events to the state.
+///
+/// The attacker turns to the target first. Flank and back attacks deal
+/// more damage, the shields may block the attacks from the sides they cover.
+/// Some attackers displace the surviving targets they hit.
+pub fn attack(state: &mut State, attacker_id: ObjId, target_id: ObjId) -> Vec<Event> {
+    assert!(can_attack(state, attacker_id, target_id));
+    let attacker = state.object(attacker_id);
+    let target = state.object(target_id);
+    let mut events = Vec::new();
+    if let Some(facing) = Dir::towards(attacker.pos, target.pos) {
+        if facing != attacker.facing {
+            events.push(Event::Turned {
+                id: attacker_id,
+                facing,
+            });
+        }
+    }
+    let side = attack_side(attacker.pos, target.pos, target.facing);
+    if gen_range(0.0, 1.0) > HIT_CHANCE {
+        events.push(Event::Missed { id: target_id });
+    } else if target.shield_sides().contains(&side) && gen_range(0.0, 1.0) < SHIELD_BLOCK_CHANCE {
+        events.push(Event::Blocked { id: target_id });
+    } else {
+        let is_critical = gen_range(0.0, 1.0) < CRITICAL_CHANCE;
+        let mut amount = gen_range(MIN_DAMAGE, MAX_DAMAGE + 1) + side.bonus();
+        if is_critical {
+            amount *= CRITICAL_MULTIPLIER;
+        }
+        push_damage(state, target_id, amount, is_critical, &mut events);
+    }
+    let is_hit = events
+        .iter()
+        .any(|event| matches!(event, Event::Damaged { .. }));
+    apply_all(state, &mut events);
+    if is_hit {
+        events.extend(forced::displace(state, attacker_id, target_id));
+    }
+    events
+}
+
+/// Executes the heal and applies the resulting events to the state.
+pub fn heal(state: &mut State, healer_id: ObjId, target_id: ObjId) -> Vec<Event> {
+    assert!(can_heal(state, healer_id, target_id));
+    let hp = state
+        .object(target_id)
+        .hp
+        .expect("target has no hit points");
+    let amount = HEAL_AMOUNT.min(hp.max - hp.current);
+    let mut events = vec![Event::Healed {
+        id: target_id,
+        amount,
+    }];
+    apply_all(state, &mut events);
+    events
+}
+
+/// Adds the damage event and, if the damage is lethal, the death one.
+pub(super) fn push_damage(
+    state: &State,
+    id: ObjId,
+    amount: i32,
+    is_critical: bool,
+    events: &mut Vec<Event>,
+) {
+    events.push(Event::Damaged {
+        id,
+        amount,
+        is_critical,
+    });
+    let hp = state.object(id).hp.expect("object has no hit points");
+    if hp.current <= amount {
+        events.push(Event::Died { id });
+    }
+}
+
+/// Applies the events and adds the vision changes they've caused.
+pub(super) fn apply_all(state: &mut State, events: &mut Vec<Event>) {
+    for event in events.iter() {
+        state.apply(event);
+    }
+    events.extend(state.update_visions());
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+use crate::core::{
+    battle::{
+        execute::{apply_all, push_damage},
+        terrain::terrain_damage,
+        Event, ObjId, State,
+    },
+    map::{Dir, Terrain},
+};
+
+const COLLISION_DAMAGE: i32 = 2;
+const FALL_DAMAGE_PER_LEVEL: i32 = 2;
+
+/// The pushed objects climb up to this many levels, the higher tiles
+/// are walls. Falling from up to this height is harmless.
+const MAX_SAFE_HEIGHT_DIFF: i16 = 1;
+
+/// Forced movement an attack causes to its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Displacement {
+    /// Knocks the target away from the attacker by up to this many tiles.
+    Push(i16),
+
+    /// Drags the target towards the attacker, but not closer than
+    /// the adjacent tile.
+    Pull(i16),
+}
+
+/// Displaces the surviving target of the attack, if the attacker can
+/// do that, and applies the events to the state.
+///
+/// A hidden unit the target bumps into is revealed to the attacker's owner.
+pub(super) fn displace(state: &mut State, attacker_id: ObjId, target_id: ObjId) -> Vec<Event> {
+    let attacker = state.object(attacker_id);
+    let displacement = match attacker.attack_displacement() {
+        Some(displacement) if state.objects.contains_key(&target_id) => displacement,
+        _ => return Vec::new(),
+    };
+    let from = attacker.pos;
+    let to = state.object(target_id).pos;
+    let (dir, distance) = match displacement {
+        Displacement::Push(distance) => (Dir::towards(from, to), distance),
+        Displacement::Pull(distance) => {
+            (Dir::towards(to, from), distance.min(from.distance(to) - 1))
+        }
+    };
+    let dir = dir.expect("objects share a tile");
+    let player = attacker.player;
+    let mut events = forced_move_events(state, target_id, dir, distance);
+    apply_all(state, &mut events);
+    let bumped = events.iter().find_map(|event| match *event {
+        Event::Bumped {
+            obstacle: Some(obstacle),
+            ..
+        } => Some(obstacle),
+        _ => None,
+    });
+    if let (Some(obstacle), Some(player)) = (bumped, player) {
+        if state.objects.contains_key(&obstacle) && !state.is_known(player, obstacle) {
+            events.push(state.reveal(player, obstacle));
+        }
+    }
+    events
+}
+
+/// Moves the object along a straight line without changing its facing.
+///
+/// The objects, the map's edges and the tiles too high to climb stop
+/// the movement and hurt everyone in the collision, the water stops it
+/// harmlessly and the ice makes the object slide further. Falling from
+/// a height and landing in a harmful terrain hurts too.
+fn forced_move_events(state: &State, id: ObjId, dir: Dir, distance: i16) -> Vec<Event> {
+    let map = state.map();
+    let object = state.object(id);
+    let mut pos = object.pos;
+    let mut path = Vec::new();
+    let mut damage: BTreeMap<ObjId, i32> = BTreeMap::new();
+    let mut bump = None;
+    let mut fall = 0;
+    let mut remaining = distance;
+    while remaining > 0 {
+        remaining -= 1;
+        let next = pos.step(dir);
+        let obstacle = if !map.is_inside(next) {
+            Some(None)
+        } else if let Some(other) = state.object_at(next) {
+            Some(Some(other))
+        } else if map.height_at(next) - map.height_at(pos) > MAX_SAFE_HEIGHT_DIFF {
+            Some(None)
+        } else {
+            None
+        };
+        if let Some(obstacle) = obstacle {
+            *damage.entry(id).or_default() += COLLISION_DAMAGE;
+            if let Some(other) = obstacle {
+                *damage.entry(other).or_default() += COLLISION_DAMAGE;
+            }
+            bump = Some(obstacle);
+            break;
+        }
+        if map.terrain_at(next) == Terrain::Water {
+            break;
+        }
+        fall += (map.height_at(pos) - map.height_at(next) - MAX_SAFE_HEIGHT_DIFF).max(0);
+        pos = next;
+        path.push(pos);
+        if remaining == 0 && map.terrain_at(pos) == Terrain::Ice {
+            remaining = 1;
+        }
+    }
+    *damage.entry(id).or_default() += i32::from(fall) * FALL_DAMAGE_PER_LEVEL;
+    if !path.is_empty() {
+        let landing = terrain_damage(object, map.terrain_at(pos)).unwrap_or(0);
+        *damage.entry(id).or_default() += landing;
+    }
+    let mut events = Vec::new();
+    if !path.is_empty() {
+        events.push(Event::Pushed { id, path });
+    }
+    if fall > 0 {
+        events.push(Event::Fell { id, levels: fall });
+    }
+    if let Some(obstacle) = bump {
+        events.push(Event::Bumped { id, dir, obstacle });
+    }
+    for (id, amount) in damage {
+        if amount > 0 && state.object(id).hp.is_some() {
+            push_damage(state, id, amount, false, &mut events);
+        }
+    }
+    events
+}
@@ -0,0 +1,125 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::core::{
+    battle::{execute::apply_all, Event, ObjId, State},
+    map::{Dir, Pos},
+};
+
+/// Shortest paths from the object's position to all tiles it can reach this turn.
+#[derive(Debug, Clone)]
+pub struct Paths {
+    start: Pos,
+
+    /// The previous tile of the path for every reachable tile.
+    prev: HashMap<Pos, Pos>,
+}
+
+impl Paths {
+    /// Runs Dijkstra's search limited by the object's move points.
+    /// Impassable terrain and the objects known to the owner block the movement,
+    /// see [Terrain::move_cost](crate::core::map::Terrain::move_cost).
+    pub fn new(state: &State, id: ObjId) -> Self {
+        let object = state.object(id);
+        // The hidden enemies must not leave holes in the range.
+        let is_blocked = |pos| match object.player {
+            Some(player) => state.known_object_at(player, pos).is_some(),
+            None => state.object_at(pos).is_some(),
+        };
+        let map = state.map();
+        let start = object.pos;
+        let mut prev = HashMap::new();
+        let mut costs = HashMap::new();
+        costs.insert(start, 0);
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((0, start.x, start.y)));
+        while let Some(Reverse((cost, x, y))) = queue.pop() {
+            let pos = Pos::new(x, y);
+            if cost > costs[&pos] {
+                continue;
+            }
+            for &next in &pos.neighbours() {
+                if !map.is_inside(next) || is_blocked(next) {
+                    continue;
+                }
+                let next_cost = match map.terrain_at(next).move_cost() {
+                    Some(step) => cost + step,
+                    None => continue,
+                };
+                let is_worse = matches!(costs.get(&next), Some(&old) if old <= next_cost);
+                if next_cost > object.move_points() || is_worse {
+                    continue;
+                }
+                costs.insert(next, next_cost);
+                prev.insert(next, pos);
+                queue.push(Reverse((next_cost, next.x, next.y)));
+            }
+        }
+        Self { start, prev }
+    }
+
+    /// The starting tile is not included.
+    pub fn reachable(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.prev.keys().copied()
+    }
+
+    pub fn is_reachable(&self, pos: Pos) -> bool {
+        self.prev.contains_key(&pos)
+    }
+
+    /// Returns the path without the starting tile.
+    pub fn path_to(&self, pos: Pos) -> Option<Vec<Pos>> {
+        let mut path = Vec::new();
+        let mut current = pos;
+        while current != self.start {
+            path.push(current);
+            current = *self.prev.get(&current)?;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+pub fn can_move(state: &State, id: ObjId, pos: Pos) -> bool {
+    Paths::new(state, id).is_reachable(pos)
+}
+
+/// Moves the object along the shortest path and applies the events to the state.
+///
+/// The move stops in front of the first hidden unit on the path
+/// and the unit is revealed to the mover's owner.
+pub fn move_to(state: &mut State, id: ObjId, pos: Pos) -> Vec<Event> {
+    let mut path = Paths::new(state, id)
+        .path_to(pos)
+        .expect("can't reach the tile");
+    assert!(!path.is_empty());
+    let hidden = path
+        .iter()
+        .position(|&pos| state.object_at(pos).is_some())
+        .map(|index| {
+            let hidden_id = state.object_at(path[index]).expect("no such object");
+            path.truncate(index);
+            hidden_id
+        });
+    let mut events = Vec::new();
+    if !path.is_empty() {
+        events.push(Event::Moved { id, path });
+    }
+    apply_all(state, &mut events);
+    if let (Some(hidden_id), Some(player)) = (hidden, state.object(id).player) {
+        if !state.is_known(player, hidden_id) {
+            events.push(state.reveal(player, hidden_id));
+        }
+    }
+    events
+}
+
+/// Turns the unit in place, applies the event to the state.
+pub fn turn(state: &mut State, id: ObjId, facing: Dir) -> Vec<Event> {
+    assert!(state.object(id).is_unit());
+    let mut events = vec![Event::Turned { id, facing }];
+    apply_all(state, &mut events);
+    events
+}
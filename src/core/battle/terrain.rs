@@ -0,0 +1,115 @@
+use crate::core::{
+    battle::{
+        execute::{apply_all, push_damage},
+        Event, ObjId, Object, State,
+    },
+    map::{Map, Pos, Terrain},
+};
+
+const FIRE_DAMAGE: i32 = 3;
+const POISON_DAMAGE: i32 = 1;
+
+/// Skills that change the terrain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Skill {
+    /// Sets the grass on fire.
+    Ignite,
+
+    /// Puts the fire out and freezes the water.
+    Frost,
+}
+
+impl Skill {
+    pub fn range(self) -> i16 {
+        match self {
+            Skill::Ignite => 4,
+            Skill::Frost => 2,
+        }
+    }
+
+    /// The terrain the skill turns the tile into, if it affects it at all.
+    fn apply_to(self, terrain: Terrain) -> Option<Terrain> {
+        match (self, terrain) {
+            (Skill::Ignite, Terrain::Grass) => Some(Terrain::Fire),
+            (Skill::Frost, Terrain::Fire) => Some(Terrain::Plain),
+            (Skill::Frost, Terrain::Water) => Some(Terrain::Ice),
+            _ => None,
+        }
+    }
+}
+
+/// Damage the terrain deals to the object standing in it.
+pub(super) fn terrain_damage(object: &Object, terrain: Terrain) -> Option<i32> {
+    match terrain {
+        _ if object.hp.is_none() => None,
+        Terrain::Fire => Some(FIRE_DAMAGE),
+        Terrain::Swamp if object.is_unit() => Some(POISON_DAMAGE),
+        _ => None,
+    }
+}
+
+pub fn can_use_skill(state: &State, id: ObjId, pos: Pos) -> bool {
+    let object = state.object(id);
+    let skill = match object.skill() {
+        Some(skill) => skill,
+        None => return false,
+    };
+    state.map().is_inside(pos)
+        && object.pos.distance(pos) <= skill.range()
+        && skill.apply_to(state.map().terrain_at(pos)).is_some()
+}
+
+/// Uses the object's skill on the tile and applies the events to the state.
+pub fn use_skill(state: &mut State, id: ObjId, pos: Pos) -> Vec<Event> {
+    assert!(can_use_skill(state, id, pos));
+    let skill = state.object(id).skill().expect("no skill");
+    let terrain = skill
+        .apply_to(state.map().terrain_at(pos))
+        .expect("bad skill target");
+    let mut events = vec![Event::TerrainChanged { pos, terrain }];
+    apply_all(state, &mut events);
+    events
+}
+
+fn is_near(map: &Map, pos: Pos, terrain: Terrain) -> bool {
+    pos.neighbours()
+        .iter()
+        .any(|&n| map.is_inside(n) && map.terrain_at(n) == terrain)
+}
+
+/// The fire next to the water is put out before it burns or spreads.
+fn is_burning(map: &Map, pos: Pos) -> bool {
+    map.terrain_at(pos) == Terrain::Fire && !is_near(map, pos, Terrain::Water)
+}
+
+/// Runs the terrain effects: the fire and the poison hurt the objects
+/// standing in them, then the fire spreads and burns out.
+/// The water puts out the fire next to it.
+pub fn end_turn(state: &mut State) -> Vec<Event> {
+    let mut events = Vec::new();
+    let map = state.map();
+    for (id, object) in state.objects() {
+        let terrain = map.terrain_at(object.pos);
+        if terrain == Terrain::Fire && !is_burning(map, object.pos) {
+            continue;
+        }
+        if let Some(amount) = terrain_damage(object, terrain) {
+            push_damage(state, id, amount, false, &mut events);
+        }
+    }
+    for pos in map.iter() {
+        let is_near_fire = pos
+            .neighbours()
+            .iter()
+            .any(|&n| map.is_inside(n) && is_burning(map, n));
+        let terrain = match map.terrain_at(pos) {
+            Terrain::Fire => Terrain::Plain,
+            Terrain::Grass if is_near_fire => Terrain::Fire,
+            Terrain::Ice if is_near_fire => Terrain::Water,
+            _ => continue,
+        };
+        events.push(Event::TerrainChanged { pos, terrain });
+    }
+    apply_all(state, &mut events);
+    events
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeSet;
+
+use crate::core::{
+    battle::{Event, ObjId, PlayerId, State},
+    map::{Map, Pos},
+};
+
+/// What a player knows about a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileVisibility {
+    /// Never seen by any of the player's units.
+    Unseen,
+
+    /// Seen before, but not visible now.
+    Seen,
+
+    /// In sight of at least one of the player's units.
+    Visible,
+}
+
+/// One player's fog of war.
+#[derive(Debug, Clone)]
+pub struct Vision {
+    width: i16,
+    tiles: Vec<TileVisibility>,
+
+    /// Other players' units in sight.
+    visible_enemies: BTreeSet<ObjId>,
+}
+
+impl Vision {
+    pub(super) fn new(map: &Map) -> Self {
+        let count = map.width() as usize * map.height() as usize;
+        Self {
+            width: map.width(),
+            tiles: vec![TileVisibility::Unseen; count],
+            visible_enemies: BTreeSet::new(),
+        }
+    }
+
+    fn index(&self, pos: Pos) -> usize {
+        pos.y as usize * self.width as usize + pos.x as usize
+    }
+
+    pub fn tile(&self, pos: Pos) -> TileVisibility {
+        self.tiles[self.index(pos)]
+    }
+
+    pub fn is_visible(&self, pos: Pos) -> bool {
+        self.tile(pos) == TileVisibility::Visible
+    }
+
+    pub fn is_enemy_visible(&self, id: ObjId) -> bool {
+        self.visible_enemies.contains(&id)
+    }
+
+    pub(super) fn reveal(&mut self, id: ObjId) {
+        self.visible_enemies.insert(id);
+    }
+}
+
+/// Checks if the unit standing at `from` can see the tile.
+///
+/// The line of sight is blocked by props and by tiles higher than
+/// both ends of the line, so the hills give a better view.
+fn is_in_sight(state: &State, from: Pos, to: Pos, range: i16) -> bool {
+    if from.distance(to) > range {
+        return false;
+    }
+    let map = state.map();
+    let top = map.height_at(from).max(map.height_at(to));
+    line(from, to).all(|pos| {
+        let is_prop = matches!(state.object_at(pos), Some(id) if !state.object(id).is_unit());
+        map.height_at(pos) <= top && !is_prop
+    })
+}
+
+/// Tiles between the two positions, the ends aren't included.
+fn line(from: Pos, to: Pos) -> impl Iterator<Item = Pos> {
+    let dx = f32::from(to.x - from.x);
+    let dy = f32::from(to.y - from.y);
+    let steps = (to.x - from.x).abs().max((to.y - from.y).abs());
+    (1..steps).map(move |i| {
+        let k = f32::from(i) / f32::from(steps);
+        let x = f32::from(from.x) + dx * k;
+        let y = f32::from(from.y) + dy * k;
+        Pos::new(x.round() as i16, y.round() as i16)
+    })
+}
+
+/// Recomputes the player's vision, the tiles visible before become seen.
+///
+/// Returns [Event::Revealed] and [Event::Hidden] for the enemy units
+/// that have entered or left the player's sight.
+pub(super) fn update(state: &State, player: PlayerId, vision: &mut Vision) -> Vec<Event> {
+    for tile in &mut vision.tiles {
+        if *tile == TileVisibility::Visible {
+            *tile = TileVisibility::Seen;
+        }
+    }
+    let viewers: Vec<_> = state
+        .objects()
+        .filter(|(_, object)| object.player == Some(player))
+        .map(|(_, object)| (object.pos, object.sight_range()))
+        .collect();
+    for pos in state.map().iter() {
+        if viewers
+            .iter()
+            .any(|&(from, range)| is_in_sight(state, from, pos, range))
+        {
+            let index = vision.index(pos);
+            vision.tiles[index] = TileVisibility::Visible;
+        }
+    }
+    let visible_enemies: BTreeSet<ObjId> = state
+        .objects()
+        .filter(|(_, object)| object.is_unit() && object.player != Some(player))
+        .filter(|(_, object)| vision.is_visible(object.pos))
+        .map(|(id, _)| id)
+        .collect();
+    let mut events = Vec::new();
+    for &id in visible_enemies.difference(&vision.visible_enemies) {
+        events.push(Event::Revealed { player, id });
+    }
+    for &id in vision.visible_enemies.difference(&visible_enemies) {
+        // The dead don't need to be hidden.
+        if state.objects.contains_key(&id) {
+            events.push(Event::Hidden { player, id });
+        }
+    }
+    vision.visible_enemies = visible_enemies;
+    events
+}
@@ -1,40 +1,345 @@
 use mq::math::Vec2;
 
+/// Isometric diamond's height relative to its width.
+const ISO_TILE_HEIGHT_K: f32 = 0.5;
+
+/// Screen height of one elevation level relative to the isometric tile's width.
+const ISO_ELEVATION_K: f32 = 0.25;
+
+/// How the map's tiles are laid out on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    /// Top-down square tiles, the elevation isn't shown.
+    Flat,
+
+    /// Diamond tiles raised by their elevation. The x axis goes
+    /// right-down and the y axis goes left-down.
+    Isometric,
+}
+
+/// What a tile is covered with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Terrain {
+    Plain,
+
+    /// Catches fire from the burning neighbours.
+    Grass,
+
+    /// Burns the objects standing in it and spreads over the grass,
+    /// becomes plain when burnt out.
+    Fire,
+
+    /// Slows the movement down, melts next to the fire.
+    Ice,
+
+    /// Can't be walked through, stops the fire and puts out the burning neighbours.
+    Water,
+
+    /// Poisons the units standing in it.
+    Swamp,
+}
+
+impl Terrain {
+    /// Move points it takes to enter the tile, `None` if it's impassable.
+    pub fn move_cost(self) -> Option<i16> {
+        match self {
+            Terrain::Water => None,
+            Terrain::Ice => Some(2),
+            _ => Some(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Map {
     width: i16,
     height: i16,
+    projection: Projection,
+
+    /// Quarter turns the map is shown rotated by, clockwise.
+    rotation: u8,
+
+    /// Elevation of every tile, row by row.
+    heights: Vec<i16>,
+
+    /// Terrain of every tile, row by row.
+    terrains: Vec<Terrain>,
 }
 
 impl Map {
+    pub fn new(width: i16, height: i16) -> Self {
+        let count = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            projection: Projection::Flat,
+            rotation: 0,
+            heights: vec![0; count],
+            terrains: vec![Terrain::Plain; count],
+        }
+    }
+
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn rotation(&self) -> u8 {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: u8) {
+        self.rotation = rotation % 4;
+    }
+
+    /// Map's size as shown on the screen, the sides swap on odd rotations.
+    pub fn rotated_size(&self) -> (i16, i16) {
+        match self.rotation {
+            0 | 2 => (self.width, self.height),
+            _ => (self.height, self.width),
+        }
+    }
+
+    /// Position of the tile in the rotated grid shown on the screen.
+    pub fn rotate_pos(&self, pos: Pos) -> Pos {
+        let (w, h) = (self.width, self.height);
+        match self.rotation {
+            0 => pos,
+            1 => Pos::new(h - 1 - pos.y, pos.x),
+            2 => Pos::new(w - 1 - pos.x, h - 1 - pos.y),
+            3 => Pos::new(pos.y, w - 1 - pos.x),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Direction on the rotated grid shown on the screen.
+    pub fn rotate_dir(&self, dir: Dir) -> Dir {
+        (0..self.rotation).fold(dir, |dir, _| dir.clockwise())
+    }
+
+    /// The map's tile shown at the rotated grid's position.
+    pub fn unrotate_pos(&self, pos: Pos) -> Pos {
+        let (w, h) = (self.width, self.height);
+        match self.rotation {
+            0 => pos,
+            1 => Pos::new(pos.y, h - 1 - pos.x),
+            2 => Pos::new(w - 1 - pos.x, h - 1 - pos.y),
+            3 => Pos::new(w - 1 - pos.y, pos.x),
+            _ => unreachable!(),
+        }
+    }
+
+    fn index(&self, pos: Pos) -> usize {
+        assert!(self.is_inside(pos), "{:?} is outside of the map", pos);
+        pos.y as usize * self.width as usize + pos.x as usize
+    }
+
+    pub fn height_at(&self, pos: Pos) -> i16 {
+        self.heights[self.index(pos)]
+    }
+
+    pub fn set_height_at(&mut self, pos: Pos, height: i16) {
+        assert!(height >= 0);
+        let index = self.index(pos);
+        self.heights[index] = height;
+    }
+
+    pub fn terrain_at(&self, pos: Pos) -> Terrain {
+        self.terrains[self.index(pos)]
+    }
+
+    pub fn set_terrain_at(&mut self, pos: Pos, terrain: Terrain) {
+        let index = self.index(pos);
+        self.terrains[index] = terrain;
+    }
+
+    pub fn max_height(&self) -> i16 {
+        self.heights.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn width(&self) -> i16 {
+        self.width
+    }
+
+    pub fn height(&self) -> i16 {
+        self.height
+    }
+
+    pub fn is_inside(&self, pos: Pos) -> bool {
+        pos.x >= 0 && pos.y >= 0 && pos.x < self.width && pos.y < self.height
+    }
+
+    /// Iterates over all positions of the map, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        let height = self.height;
+        (0..height).flat_map(move |y| (0..width).map(move |x| Pos { x, y }))
+    }
+
     /// Given a screen pixel, translate it into a Pos.
+    ///
+    /// For the isometric projection this is the front-most tile
+    /// whose column (the top and the sides) covers the pixel.
     pub fn pixel_to_pos(&self, pixel: Vec2, aspect_ratio: f32) -> Option<Pos> {
         let (l, t, r, b, s) = self.layout(aspect_ratio);
         let x = pixel.x;
         let y = pixel.y;
         if x < l || x > r || y < t || y > b {
-            return None
+            return None;
+        }
+        if self.projection == Projection::Isometric {
+            let mut positions: Vec<Pos> = self.iter().collect();
+            positions.sort_by_key(|&pos| {
+                let rotated = self.rotate_pos(pos);
+                -(rotated.x + rotated.y)
+            });
+            return positions
+                .into_iter()
+                .find(|&pos| self.column_contains(pos, pixel, aspect_ratio));
         }
         let mut pos = Pos { x: 0, y: 0 };
         pos.x = ((x - l) / s).floor() as i16;
         pos.y = ((y - t) / s).floor() as i16;
-        Some(pos)
+        let (w, h) = self.rotated_size();
+        // The right and the bottom edges belong to the last tiles.
+        pos.x = pos.x.min(w - 1);
+        pos.y = pos.y.min(h - 1);
+        Some(self.unrotate_pos(pos))
     }
 
-    /// Given a screen aspect ratio, layout the map. 
+    fn column_contains(&self, pos: Pos, pixel: Vec2, aspect_ratio: f32) -> bool {
+        let half = self.tile_size(aspect_ratio) * 0.5;
+        let center = self.tile_center(pos, aspect_ratio);
+        let dx = (pixel.x - center.x).abs();
+        if dx > half.x {
+            return false;
+        }
+        // Distance from the diamond's center to its edge at this x.
+        let edge = half.y * (1.0 - dx / half.x);
+        let top = center.y - edge;
+        let ground_level = f32::from(self.height_at(pos) + 1) * self.elevation_step(aspect_ratio);
+        let bottom = center.y + edge + ground_level;
+        pixel.y >= top && pixel.y <= bottom
+    }
+
+    /// Given a Pos, returns the screen point of its tile's top left corner.
     ///
-    /// Returns (left, top, right, bottom, tile_size)
+    /// For the isometric projection that's the top corner of the tile's
+    /// diamond at the zero elevation.
+    pub fn pos_to_pixel(&self, pos: Pos, aspect_ratio: f32) -> Vec2 {
+        let rotated = self.rotate_pos(pos);
+        let grid = Vec2::new(f32::from(rotated.x), f32::from(rotated.y));
+        self.grid_to_screen(grid, aspect_ratio)
+    }
+
+    /// Converts a point of the rotated grid (in tiles) to the screen
+    /// point at the zero elevation.
+    fn grid_to_screen(&self, grid: Vec2, aspect_ratio: f32) -> Vec2 {
+        let (l, t, _, _, s) = self.layout(aspect_ratio);
+        match self.projection {
+            Projection::Flat => Vec2::new(l + grid.x * s, t + grid.y * s),
+            Projection::Isometric => {
+                let half = self.tile_size(aspect_ratio) * 0.5;
+                let step = self.elevation_step(aspect_ratio);
+                let (_, rotated_h) = self.rotated_size();
+                let origin_x = l + f32::from(rotated_h) * half.x;
+                let origin_y = t + f32::from(self.max_height()) * step;
+                Vec2::new(
+                    origin_x + (grid.x - grid.y) * half.x,
+                    origin_y + (grid.x + grid.y) * half.y,
+                )
+            }
+        }
+    }
+
+    /// Projects a point given in the map's tiles (tile centers are at
+    /// `pos + 0.5`) and raised by `levels` of elevation as if the map was
+    /// rotated by `quarters`. Fractional quarters are used for the
+    /// rotation transitions.
+    pub fn project(&self, point: Vec2, levels: f32, quarters: f32, aspect_ratio: f32) -> Vec2 {
+        let angle = quarters * std::f32::consts::FRAC_PI_2;
+        let (sin, cos) = angle.sin_cos();
+        let center = Vec2::new(f32::from(self.width), f32::from(self.height)) * 0.5;
+        let d = point - center;
+        let rotated = Vec2::new(cos * d.x - sin * d.y, sin * d.x + cos * d.y);
+        // Both projections are symmetric, so the current rotated size
+        // gives the same map center for any angle.
+        let (w, h) = self.rotated_size();
+        let grid = rotated + Vec2::new(f32::from(w), f32::from(h)) * 0.5;
+        let lift = levels * self.elevation_step(aspect_ratio);
+        self.grid_to_screen(grid, aspect_ratio) - Vec2::new(0.0, lift)
+    }
+
+    /// Center of the tile's top surface, including the elevation.
+    pub fn tile_center(&self, pos: Pos, aspect_ratio: f32) -> Vec2 {
+        let point = Vec2::new(f32::from(pos.x) + 0.5, f32::from(pos.y) + 0.5);
+        let levels = f32::from(self.height_at(pos));
+        self.project(point, levels, f32::from(self.rotation), aspect_ratio)
+    }
+
+    /// How high the tile is raised on the screen.
+    pub fn elevation(&self, pos: Pos, aspect_ratio: f32) -> f32 {
+        f32::from(self.height_at(pos)) * self.elevation_step(aspect_ratio)
+    }
+
+    /// Screen height of one elevation level, zero for the flat projection.
+    pub fn elevation_step(&self, aspect_ratio: f32) -> f32 {
+        match self.projection {
+            Projection::Flat => 0.0,
+            Projection::Isometric => {
+                let (_, _, _, _, s) = self.layout(aspect_ratio);
+                s * ISO_ELEVATION_K
+            }
+        }
+    }
+
+    /// Screen size of a tile: a square or an isometric diamond.
+    pub fn tile_size(&self, aspect_ratio: f32) -> Vec2 {
+        let (_, _, _, _, s) = self.layout(aspect_ratio);
+        match self.projection {
+            Projection::Flat => Vec2::new(s, s),
+            Projection::Isometric => Vec2::new(s, s * ISO_TILE_HEIGHT_K),
+        }
+    }
+
+    /// Given a screen aspect ratio, layout the map.
+    ///
+    /// The map is centered in the `-aspect_ratio..aspect_ratio, -1..1`
+    /// camera space.
+    ///
+    /// Returns (left, top, right, bottom, tile_size), the tile size
+    /// is the diamond's width for the isometric projection.
     pub fn layout(&self, aspect_ratio: f32) -> (f32, f32, f32, f32, f32) {
+        let (w, h) = self.rotated_size();
+        let w = f32::from(w);
+        let h = f32::from(h);
+        // Map's size in tile sizes.
+        let (size_w, size_h) = match self.projection {
+            Projection::Flat => (w, h),
+            Projection::Isometric => {
+                // One more level for the sides of the ground tiles.
+                let levels = f32::from(self.max_height() + 1);
+                (
+                    (w + h) * 0.5,
+                    (w + h) * 0.5 * ISO_TILE_HEIGHT_K + levels * ISO_ELEVATION_K,
+                )
+            }
+        };
         // We use aspect ratio because aspect ratio is screen width / screen height
-        let wsize: f32 = aspect_ratio / f32::from(self.width);
-        let hsize: f32 = 1.0 / f32::from(self.height);
+        let wsize: f32 = aspect_ratio * 2.0 / size_w;
+        let hsize: f32 = 2.0 / size_h;
         let mut tile_size = wsize;
         if hsize < wsize {
             tile_size = hsize;
         }
-        let l = (aspect_ratio * 2.0 - tile_size * f32::from(self.width)) / 2.0;
-        let r = aspect_ratio * 2.0 - l;
-        let t = (2.0 - tile_size * f32::from(self.height)) / 2.0;
-        let b = 2.0 - t;
+        let l = -tile_size * size_w / 2.0;
+        let r = -l;
+        let t = -tile_size * size_h / 2.0;
+        let b = -t;
         (l, t, r, b, tile_size)
     }
 }
@@ -42,8 +347,78 @@ impl Map {
 /// Represents a position in the map
 ///
 /// The upper left part of the map is position (0,0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Pos {
-    x: i16,
-    y: i16,
+    pub x: i16,
+    pub y: i16,
+}
+
+impl Pos {
+    pub fn new(x: i16, y: i16) -> Self {
+        Self { x, y }
+    }
+
+    /// Manhattan distance.
+    pub fn distance(self, other: Pos) -> i16 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// The four orthogonal neighbours: north, east, south and west.
+    pub fn neighbours(self) -> [Pos; 4] {
+        let mut neighbours = [self; 4];
+        for (neighbour, dir) in neighbours.iter_mut().zip(&Dir::ALL) {
+            *neighbour = self.step(*dir);
+        }
+        neighbours
+    }
+
+    pub fn step(self, dir: Dir) -> Pos {
+        let (dx, dy) = dir.delta();
+        Pos::new(self.x + dx, self.y + dy)
+    }
+}
+
+/// One of the four grid directions, north is towards the zero y.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dir {
+    North,
+    East,
+    South,
+    West,
 }
 
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::North, Dir::East, Dir::South, Dir::West];
+
+    pub fn delta(self) -> (i16, i16) {
+        match self {
+            Dir::North => (0, -1),
+            Dir::East => (1, 0),
+            Dir::South => (0, 1),
+            Dir::West => (-1, 0),
+        }
+    }
+
+    pub fn clockwise(self) -> Dir {
+        match self {
+            Dir::North => Dir::East,
+            Dir::East => Dir::South,
+            Dir::South => Dir::West,
+            Dir::West => Dir::North,
+        }
+    }
+
+    /// The direction from one tile to the other along the longer axis,
+    /// the horizontal one wins the ties.
+    pub fn towards(from: Pos, to: Pos) -> Option<Dir> {
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        if dx == 0 && dy == 0 {
+            None
+        } else if dx.abs() >= dy.abs() {
+            Some(if dx > 0 { Dir::East } else { Dir::West })
+        } else {
+            Some(if dy > 0 { Dir::South } else { Dir::North })
+        }
+    }
+}
@@ -1,2 +1,3 @@
+pub mod battle;
 mod class;
-mod map;
+pub mod map;
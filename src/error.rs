@@ -6,7 +6,7 @@ use std::path::PathBuf;
 #[derive(Debug, derive_more::From)]
 pub enum AError {
     UiError(ui::Error),
-    //SceneError(zscene::Error),
+    SceneError(zscene::Error),
     RonDeserializeError {
         error: ron::de::Error,
         path: PathBuf,
@@ -14,6 +14,8 @@ pub enum AError {
     IOError(io::Error),
     MqFileError(mq::file::FileError),
     MqFontError(mq::text::FontError),
+    #[from(ignore)]
+    NoDefaultTheme,
 }
 
 impl AError {
@@ -26,7 +28,7 @@ impl fmt::Display for AError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             AError::UiError(ref e) => write!(f, "ZGUI Error: {}", e),
-            //AError::SceneError(ref e) => write!(f, "ZScene Error: {}", e),
+            AError::SceneError(ref e) => write!(f, "ZScene Error: {}", e),
             AError::RonDeserializeError { error, path } => {
                 let s = path.to_str().unwrap_or("<no path>");
                 write!(f, "Can't deserialize '{}': {}", s, error)
@@ -34,6 +36,7 @@ impl fmt::Display for AError {
             AError::IOError(ref e) => write!(f, "IO Error: {}", e),
             AError::MqFileError(ref e) => write!(f, "Macroquad File error: {}", e),
             AError::MqFontError(ref e) => write!(f, "Macroquad Font error: {}", e),
+            AError::NoDefaultTheme => write!(f, "No 'default' theme in themes.ron"),
         }
     }
 }
@@ -42,11 +45,12 @@ impl error::Error for AError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             AError::UiError(ref e) => Some(e),
-            //AError::SceneError(ref e) => Some(e),
+            AError::SceneError(ref e) => Some(e),
             AError::RonDeserializeError { error, .. } => Some(error),
             AError::IOError(ref e) => Some(e),
             AError::MqFileError(ref e) => Some(e),
             AError::MqFontError(ref e) => Some(e),
+            AError::NoDefaultTheme => None,
         }
     }
 }
@@ -1,15 +1,36 @@
 use std::time::Duration;
 
-use mq::{input, window};
+use mq::{
+    input::{self, KeyCode},
+    window,
+};
 
 mod assets;
 mod core;
 mod error;
 mod screen;
+mod settings;
 mod utils;
 
 type AResult<T = ()> = Result<T, error::AError>;
 
+/// Keys used by the GUI's focus navigation and text editing.
+const GUI_KEYS: [KeyCode; 13] = [
+    KeyCode::Tab,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Enter,
+    KeyCode::KpEnter,
+    KeyCode::Space,
+    KeyCode::Escape,
+    KeyCode::Backspace,
+    KeyCode::Delete,
+    KeyCode::Home,
+    KeyCode::End,
+];
+
 struct MainState {
     screen: screen::ScreenStack,
 }
@@ -32,6 +53,29 @@ impl MainState {
         if input::is_mouse_button_pressed(input::MouseButton::Left) {
             self.screen.click(pos)?;
         }
+        if input::is_mouse_button_released(input::MouseButton::Left) {
+            self.screen.release(pos)?;
+        }
+        let (_, wheel) = input::mouse_wheel();
+        if wheel != 0.0 {
+            // The wheel's units differ between the platforms, so only the direction is used.
+            self.screen.scroll(pos, wheel.signum())?;
+        }
+        let shift =
+            input::is_key_down(KeyCode::LeftShift) || input::is_key_down(KeyCode::RightShift);
+        for &code in &GUI_KEYS {
+            if input::is_key_pressed(code) {
+                self.screen.press_key(ui::KeyPress { code, shift })?;
+            }
+        }
+        // The queue is never cleared by macroquad and is popped from the end.
+        let mut chars = Vec::new();
+        while let Some(c) = input::get_char_pressed() {
+            chars.push(c);
+        }
+        for &c in chars.iter().rev() {
+            self.screen.type_char(c)?;
+        }
         // Update the game state.
         let dtime = Duration::from_secs_f32(mq::time::get_frame_time());
         self.screen.update(dtime)?;
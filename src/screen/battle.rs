@@ -1,36 +1,444 @@
-// TODO:
-// - Successfully render map
+use std::time::Duration;
 
+use mq::math::Vec2;
+use ui::Gui;
+
+use crate::{
+    assets,
+    core::{
+        battle::{self, Hp, ObjId, Object, Paths, PlayerId, State, TileVisibility},
+        map::{Dir, Map, Pos, Projection, Terrain},
+    },
+    screen::{Screen, StackCommand},
+    settings::Settings,
+    utils, AResult,
+};
+
+mod view;
+
+use self::view::{BattleView, HighlightKind};
+
+const UNIT_HP: i32 = 10;
+
+/// The human player, the battle is shown through this player's vision.
+const PLAYER: PlayerId = PlayerId(0);
+
+/// The human player's faction, its theme styles the battle's GUI.
+// TODO: Load the factions from a scenario.
+const PLAYER_FACTION: &str = "wolves";
+
+/// Highlights of the selected unit's actions.
+const SELECTION_HIGHLIGHTS: [HighlightKind; 4] = [
+    HighlightKind::Movement,
+    HighlightKind::Attack,
+    HighlightKind::Path,
+    HighlightKind::Area,
+];
+
+#[derive(Copy, Clone, Debug)]
+enum Message {
+    RotateLeft,
+    RotateRight,
+    Skill,
+    EndTurn,
+    Cancel,
+}
+
+/// The rotation buttons are shown only for the isometric maps.
+fn make_gui(projection: Projection) -> AResult<Gui<Message>> {
+    let mut gui = Gui::new()
+        .with_theme(assets::get().theme(PLAYER_FACTION).clone())
+        .with_cancel_message(Message::Cancel);
+    let font = assets::get().font;
+    let h = utils::line_heights().big;
+    let hint_h = utils::line_heights().small;
+    let mut layout = Box::new(ui::HLayout::new().with_spacing(h / 4.0));
+    let mut buttons = vec![
+        (
+            "skill",
+            Message::Skill,
+            "Use the selected unit's skill:\nsnipers set grass on fire,\nmonks freeze water",
+        ),
+        (
+            "end turn",
+            Message::EndTurn,
+            "End the turn:\nthe fire burns the units\nand spreads to the grass",
+        ),
+    ];
+    if projection == Projection::Isometric {
+        buttons.push(("<", Message::RotateLeft, "Rotate the map\ncounterclockwise"));
+        buttons.push((">", Message::RotateRight, "Rotate the map\nclockwise"));
+    }
+    for &(text, message, hint) in &buttons {
+        let text = ui::Drawable::text(text, font);
+        let button = Box::new(ui::Button::new(text, h, gui.sender(), message)?);
+        let tooltip = ui::Tooltip::from_text(button, None, hint, font, hint_h)?;
+        layout.add(Box::new(tooltip));
+    }
+    let layout = utils::add_offsets_and_bg(layout, utils::OFFSET_SMALL)?;
+    let anchor = ui::Anchor(ui::HAnchor::Right, ui::VAnchor::Bottom);
+    gui.add(&ui::pack(layout), anchor);
+    Ok(gui)
+}
+
+// TODO: Load the objects from a scenario.
+fn make_state(projection: Projection) -> State {
+    let mut map = Map::new(10, 8).with_projection(projection);
+    let hills = [
+        (Pos::new(6, 4), 2),
+        (Pos::new(7, 4), 1),
+        (Pos::new(6, 5), 1),
+        (Pos::new(7, 5), 1),
+        (Pos::new(8, 6), 3),
+        (Pos::new(0, 0), 1),
+    ];
+    for &(pos, height) in &hills {
+        map.set_height_at(pos, height);
+    }
+    let terrains = [
+        (Terrain::Grass, Pos::new(2, 2), Pos::new(5, 3)),
+        (Terrain::Water, Pos::new(8, 2), Pos::new(9, 3)),
+        (Terrain::Ice, Pos::new(1, 1), Pos::new(2, 1)),
+        (Terrain::Swamp, Pos::new(0, 6), Pos::new(1, 7)),
+    ];
+    for &(terrain, from, to) in &terrains {
+        for pos in map.iter() {
+            if pos.x >= from.x && pos.x <= to.x && pos.y >= from.y && pos.y <= to.y {
+                map.set_terrain_at(pos, terrain);
+            }
+        }
+    }
+    let mut state = State::new(map);
+    let objects = [
+        ("boulder", Pos::new(4, 3), None, None),
+        ("crate", Pos::new(5, 5), None, Some(3)),
+        ("barricade", Pos::new(3, 2), None, Some(6)),
+        ("warrior", Pos::new(2, 5), Some(PlayerId(0)), Some(UNIT_HP)),
+        ("lancer", Pos::new(3, 6), Some(PlayerId(0)), Some(UNIT_HP)),
+        ("monk", Pos::new(1, 4), Some(PlayerId(0)), Some(UNIT_HP)),
+        ("knight", Pos::new(4, 5), Some(PlayerId(0)), Some(UNIT_HP)),
+        ("sniper", Pos::new(7, 1), Some(PlayerId(1)), Some(UNIT_HP)),
+        ("warrior", Pos::new(6, 2), Some(PlayerId(1)), Some(UNIT_HP)),
+        ("phalanx", Pos::new(5, 1), Some(PlayerId(1)), Some(UNIT_HP)),
+        ("golem", Pos::new(3, 1), Some(PlayerId(1)), Some(UNIT_HP)),
+        ("summoner", Pos::new(8, 1), Some(PlayerId(1)), Some(UNIT_HP)),
+    ];
+    for &(obj_type, pos, player, hp) in &objects {
+        let hp = hp.map(Hp::new);
+        // The sides start facing each other.
+        let facing = if player == Some(PLAYER) {
+            Dir::North
+        } else {
+            Dir::South
+        };
+        state.add_object(Object {
+            obj_type: obj_type.into(),
+            pos,
+            facing,
+            player,
+            hp,
+        });
+    }
+    state
+}
+
+#[derive(Debug)]
 pub struct BattleScreen {
-    scene: Scene,
+    state: State,
+    view: BattleView,
+    gui: Gui<Message>,
+    selected: Option<ObjId>,
+    hovered_tile: Option<Pos>,
+
+    /// The object under the cursor, its sprite may cover other tiles.
+    hovered_obj: Option<ObjId>,
+
+    /// The next click uses the selected unit's skill.
+    is_skill_mode: bool,
+}
+
+impl BattleScreen {
+    pub fn new(projection: Projection, settings: &Settings) -> AResult<Self> {
+        let state = make_state(projection);
+        let mut view = BattleView::new(&state, PLAYER, utils::aspect_ratio());
+        view.set_animation_speed(settings.animation_speed);
+        let gui = make_gui(projection)?;
+        let mut this = Self {
+            state,
+            view,
+            gui,
+            selected: None,
+            hovered_tile: None,
+            hovered_obj: None,
+            is_skill_mode: false,
+        };
+        this.update_fog();
+        Ok(this)
+    }
+
+    fn select(&mut self, id: Option<ObjId>) {
+        self.selected = id;
+        self.is_skill_mode = false;
+        self.view.set_selected(id);
+        self.update_overlay();
+    }
+
+    /// The object the click or the action under the cursor is aimed at.
+    fn target(&self, picked: Option<ObjId>, tile: Option<Pos>) -> Option<ObjId> {
+        picked.or_else(|| tile.and_then(|tile| self.state.known_object_at(PLAYER, tile)))
+    }
+
+    fn update_fog(&mut self) {
+        let vision = self.state.vision(PLAYER);
+        let tiles = |visibility| {
+            self.state
+                .map()
+                .iter()
+                .filter(|&pos| vision.tile(pos) == visibility)
+                .collect()
+        };
+        let unexplored = tiles(TileVisibility::Unseen);
+        let fog = tiles(TileVisibility::Seen);
+        let overlay = self.view.overlay_mut();
+        overlay.set(HighlightKind::Unexplored, unexplored);
+        overlay.set(HighlightKind::Fog, fog);
+    }
+
+    fn update_overlay(&mut self) {
+        let selected = match self.selected {
+            Some(id) => id,
+            None => {
+                let overlay = self.view.overlay_mut();
+                for &kind in &SELECTION_HIGHLIGHTS {
+                    overlay.clear(kind);
+                }
+                return;
+            }
+        };
+        if self.is_skill_mode {
+            self.update_skill_overlay(selected);
+            return;
+        }
+        let object = self.state.object(selected);
+        let start = object.pos;
+        let range = object.attack_range();
+        let paths = Paths::new(&self.state, selected);
+        let movement = paths.reachable().collect();
+        let attack = self
+            .state
+            .map()
+            .iter()
+            .filter(|&pos| pos != start && pos.distance(start) <= range)
+            .collect();
+        let path = match self.hovered_tile.and_then(|tile| paths.path_to(tile)) {
+            Some(path) if !path.is_empty() => std::iter::once(start).chain(path).collect(),
+            _ => Vec::new(),
+        };
+        let area = match self.target(self.hovered_obj, self.hovered_tile) {
+            Some(target)
+                if battle::can_attack(&self.state, selected, target)
+                    || battle::can_heal(&self.state, selected, target) =>
+            {
+                vec![self.state.object(target).pos]
+            }
+            _ => Vec::new(),
+        };
+        let overlay = self.view.overlay_mut();
+        overlay.set(HighlightKind::Movement, movement);
+        overlay.set(HighlightKind::Attack, attack);
+        overlay.set(HighlightKind::Path, path);
+        overlay.set(HighlightKind::Area, area);
+    }
+
+    /// Highlights the tiles the skill can be used on.
+    fn update_skill_overlay(&mut self, selected: ObjId) {
+        let state = &self.state;
+        let targets = state
+            .map()
+            .iter()
+            .filter(|&pos| battle::can_use_skill(state, selected, pos))
+            .collect();
+        let area = match self.hovered_tile {
+            Some(tile) if battle::can_use_skill(state, selected, tile) => vec![tile],
+            _ => Vec::new(),
+        };
+        let overlay = self.view.overlay_mut();
+        overlay.clear(HighlightKind::Movement);
+        overlay.clear(HighlightKind::Path);
+        overlay.set(HighlightKind::Attack, targets);
+        overlay.set(HighlightKind::Area, area);
+    }
+
+    fn toggle_skill_mode(&mut self) {
+        let has_skill = match self.selected {
+            Some(id) => self.state.object(id).skill().is_some(),
+            None => false,
+        };
+        self.is_skill_mode = has_skill && !self.is_skill_mode;
+        self.update_overlay();
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::RotateLeft => self.view.rotate(-1),
+            Message::RotateRight => self.view.rotate(1),
+            Message::Skill => self.toggle_skill_mode(),
+            Message::EndTurn => {
+                // TODO: Pass the turn to the AI.
+                let events = battle::end_turn(&mut self.state);
+                self.show_events(&events);
+            }
+            Message::Cancel if self.is_skill_mode => self.toggle_skill_mode(),
+            Message::Cancel => self.select(None),
+        }
+    }
+
+    fn show_events(&mut self, events: &[battle::Event]) {
+        for event in events {
+            match *event {
+                battle::Event::Died { id } | battle::Event::Hidden { player: PLAYER, id }
+                    if self.selected == Some(id) =>
+                {
+                    self.select(None);
+                }
+                // Other players' visions are the AI's business.
+                battle::Event::Revealed { player, .. } | battle::Event::Hidden { player, .. }
+                    if player != PLAYER =>
+                {
+                    continue;
+                }
+                // The view knows what's hidden at this point of the events,
+                // the state may have already forgotten the dead.
+                battle::Event::Damaged { id, .. }
+                | battle::Event::Healed { id, .. }
+                | battle::Event::Missed { id }
+                | battle::Event::Blocked { id }
+                | battle::Event::Fell { id, .. }
+                | battle::Event::Bumped { id, .. }
+                    if self.view.is_hidden(id) =>
+                {
+                    continue;
+                }
+                _ => {}
+            }
+            self.view.show_event(event);
+        }
+        self.update_fog();
+        self.update_overlay();
+    }
 }
 
 impl Screen for BattleScreen {
     fn update(&mut self, duration: Duration) -> AResult<StackCommand> {
         // TODO: This should, if on enemy turn, execute a series of action by
         // the AI
-        self.scene.tick(duration);
+        self.view.update(duration);
         Ok(StackCommand::None)
     }
 
     fn draw(&self) -> AResult {
-        // TODO
-        self.scene.draw();
+        self.view.scene().draw();
+        self.gui.draw();
         Ok(())
     }
 
     fn click(&mut self, pos: Vec2) -> AResult<StackCommand> {
+        if let Some(message) = self.gui.click(pos) {
+            self.handle_message(message);
+            return Ok(StackCommand::None);
+        }
         // TODO: This should handle click if turn is player turn
+        if self.view.is_rotating() {
+            return Ok(StackCommand::None);
+        }
+        let picked = self.view.pick(pos);
+        let tile = self.view.pos_at(pos);
+        log::trace!(
+            "BattleScreen: click: pos={:?}, tile={:?}, picked={:?}",
+            pos,
+            tile,
+            picked
+        );
+        let target = self.target(picked, tile);
+        if let (true, Some(selected), Some(tile)) = (self.is_skill_mode, self.selected, tile) {
+            self.is_skill_mode = false;
+            if battle::can_use_skill(&self.state, selected, tile) {
+                let events = battle::use_skill(&mut self.state, selected, tile);
+                self.show_events(&events);
+            } else {
+                self.update_overlay();
+            }
+            return Ok(StackCommand::None);
+        }
+        match (self.selected, target, tile) {
+            // Clicking the selected unit turns it around clockwise.
+            (Some(selected), Some(target), _) if selected == target => {
+                let facing = self.state.object(selected).facing.clockwise();
+                let events = battle::turn(&mut self.state, selected, facing);
+                self.show_events(&events);
+            }
+            (Some(selected), None, Some(tile)) if battle::can_move(&self.state, selected, tile) => {
+                let events = battle::move_to(&mut self.state, selected, tile);
+                self.show_events(&events);
+            }
+            (Some(selected), Some(target), _)
+                if battle::can_attack(&self.state, selected, target) =>
+            {
+                let events = battle::attack(&mut self.state, selected, target);
+                self.show_events(&events);
+            }
+            (Some(selected), Some(target), _)
+                if battle::can_heal(&self.state, selected, target) =>
+            {
+                let events = battle::heal(&mut self.state, selected, target);
+                self.show_events(&events);
+            }
+            (_, Some(id), _) if self.state.object(id).is_unit() => self.select(Some(id)),
+            _ => self.select(None),
+        }
         Ok(StackCommand::None)
     }
 
     fn resize(&mut self, aspect_ratio: f32) {
-        // TODO
+        self.gui.resize_if_needed(aspect_ratio);
+        self.view.resize(&self.state, aspect_ratio);
+        self.view.set_selected(self.selected);
+        self.update_fog();
+        self.update_overlay();
     }
 
     fn move_mouse(&mut self, pos: Vec2) -> AResult {
-        // TODO
+        self.gui.move_mouse(pos);
+        self.view.move_mouse(pos);
+        let tile = self.view.pos_at(pos);
+        let obj = self.view.pick(pos);
+        if tile != self.hovered_tile || obj != self.hovered_obj {
+            self.hovered_tile = tile;
+            self.hovered_obj = obj;
+            self.update_overlay();
+        }
+        Ok(())
+    }
+
+    fn release(&mut self, pos: Vec2) -> AResult {
+        self.gui.release(pos);
+        Ok(())
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) -> AResult {
+        self.gui.scroll(pos, delta);
         Ok(())
     }
-}
 
+    fn type_char(&mut self, c: char) -> AResult {
+        self.gui.type_char(c);
+        Ok(())
+    }
+
+    fn press_key(&mut self, key: ui::KeyPress) -> AResult<StackCommand> {
+        if let Some(message) = self.gui.press_key(key) {
+            self.handle_message(message);
+        }
+        Ok(StackCommand::None)
+    }
+}
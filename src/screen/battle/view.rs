@@ -0,0 +1,844 @@
+use std::{collections::HashMap, time::Duration};
+
+use mq::{color::Color, math::Vec2};
+use zscene::{
+    action, Action, Atlas, Boxed, Easing, Facing, HoverEvent, Layer, PickMode, Scene, SortMode,
+    Sprite, SpriteId, TileLayer,
+};
+
+use crate::{
+    assets,
+    core::{
+        battle::{Event, ObjId, Object, PlayerId, State},
+        map::{Dir, Map, Pos, Projection, Terrain},
+    },
+};
+
+use self::combat_text::{CombatText, Kind};
+
+pub use self::overlay::{Kind as HighlightKind, Overlay};
+
+mod combat_text;
+mod overlay;
+
+const TRANSPARENT: Color = Color::new(1.0, 1.0, 1.0, 0.0);
+const SHADOW_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.5);
+const OBJECT_COLOR: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+const OBJECT_COLOR_HOVERED: Color = Color::new(1.0, 1.0, 0.8, 1.0);
+const OBJECT_COLOR_SELECTED: Color = Color::new(0.8, 1.0, 0.8, 1.0);
+
+/// Object sprite's height relative to the tile size.
+const OBJECT_SPRITE_HEIGHT_K: f32 = 1.5;
+
+/// Combat text's line height relative to the tile size.
+const COMBAT_TEXT_HEIGHT_K: f32 = 0.3;
+
+const DEATH_FADE_TIME: Duration = Duration::from_millis(500);
+
+/// Time for the enemies to appear or to vanish in the fog of war.
+const VISION_FADE_TIME: Duration = Duration::from_millis(300);
+
+/// Isometric depth biases relative to the tile size. Blocks and shadows
+/// must be below the objects' `sub_tile_z` to stay under the objects.
+const BLOCK_Z: f32 = -0.15;
+const BLOCK_LEVEL_Z: f32 = 0.001;
+const SHADOW_Z: f32 = -0.12;
+
+/// Isometric block's texture height relative to its width.
+const BLOCK_HEIGHT_K: f32 = 0.75;
+
+const BLOCK_COLOR: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+
+/// Blocks covering the selected unit or the one under the cursor.
+const BLOCK_COLOR_OCCLUDING: Color = Color::new(1.0, 1.0, 1.0, 0.35);
+
+const ROTATION_TIME: Duration = Duration::from_millis(400);
+
+/// Optional frame of the units facing away from the camera.
+const BACK_FRAME: &str = "back";
+
+/// Time to move one tile.
+const MOVE_STEP_TIME: Duration = Duration::from_millis(150);
+
+/// Time to be pushed through one tile.
+const PUSH_STEP_TIME: Duration = Duration::from_millis(90);
+
+/// How far a bumped object jerks towards the obstacle, relative to the tile.
+const BUMP_K: f32 = 0.15;
+const BUMP_TIME: Duration = Duration::from_millis(80);
+
+#[derive(Debug, Clone)]
+pub struct Layers {
+    pub bg: Layer,
+    pub overlay: Layer,
+    pub shadows: Layer,
+    pub objects: Layer,
+    pub text: Layer,
+}
+
+impl Layers {
+    fn new() -> Self {
+        Self {
+            bg: Layer::new().with_pickable(false),
+            overlay: Layer::new().with_pickable(false),
+            shadows: Layer::new().with_pickable(false),
+            objects: Layer::new().with_sort_mode(SortMode::Y),
+            text: Layer::new().with_pickable(false),
+        }
+    }
+
+    /// The isometric terrain blocks share the objects layer, so the overlay
+    /// is drawn over the objects to not be hidden by the blocks.
+    fn sorted(self, projection: Projection) -> Vec<Layer> {
+        match projection {
+            Projection::Flat => vec![self.bg, self.overlay, self.shadows, self.objects, self.text],
+            Projection::Isometric => {
+                vec![self.bg, self.shadows, self.objects, self.overlay, self.text]
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ObjectSprites {
+    sprite: Sprite,
+    shadow: Sprite,
+    pos: Pos,
+    facing: Dir,
+
+    /// `sub_tile_z` scaled to the tile size.
+    z: f32,
+
+    /// Enemies outside of the player's vision are transparent.
+    is_hidden: bool,
+
+    /// Duration of the last forced movement, a bump waits for it to end.
+    push_time: Duration,
+}
+
+/// One elevation level of an isometric tile's column.
+#[derive(Debug)]
+struct Block {
+    sprite: Sprite,
+    pos: Pos,
+    level: i16,
+
+    /// Depth bias in the objects layer, it doesn't depend on the rotation.
+    z: f32,
+
+    is_occluding: bool,
+}
+
+/// An animated transition between two map rotations.
+#[derive(Debug)]
+struct Rotation {
+    /// Quarter turns, the map is already set to the final one.
+    from: f32,
+    to: f32,
+    time: Duration,
+}
+
+#[derive(Debug)]
+pub struct BattleView {
+    /// The player whose vision is shown.
+    player: PlayerId,
+    map: Map,
+    aspect_ratio: f32,
+    layers: Layers,
+    scene: Scene,
+    combat_text: CombatText,
+    overlay: Overlay,
+    sprites: HashMap<ObjId, ObjectSprites>,
+    sprite_to_obj: HashMap<SpriteId, ObjId>,
+    /// The flat projection's terrain.
+    terrain_tiles: Option<TileLayer>,
+
+    blocks: Vec<Block>,
+    rotation: Option<Rotation>,
+    cursor: Option<Vec2>,
+    hovered: Option<ObjId>,
+    selected: Option<ObjId>,
+}
+
+impl BattleView {
+    pub fn new(state: &State, player: PlayerId, aspect_ratio: f32) -> Self {
+        let map = state.map().clone();
+        let mut layers = Layers::new();
+        let scene = Scene::new(layers.clone().sorted(map.projection()));
+        let combat_text = make_combat_text(&layers, &map, aspect_ratio);
+        let overlay = make_overlay(&mut layers, &map, aspect_ratio);
+        let mut this = Self {
+            player,
+            map,
+            aspect_ratio,
+            layers,
+            scene,
+            combat_text,
+            overlay,
+            sprites: HashMap::new(),
+            sprite_to_obj: HashMap::new(),
+            terrain_tiles: None,
+            blocks: Vec::new(),
+            rotation: None,
+            cursor: None,
+            hovered: None,
+            selected: None,
+        };
+        this.add_sprites(state);
+        this
+    }
+
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    pub fn overlay_mut(&mut self) -> &mut Overlay {
+        &mut self.overlay
+    }
+
+    /// Multiplier of the scene's actions and the map's rotation speed.
+    pub fn set_animation_speed(&mut self, speed: f32) {
+        self.scene.set_time_scale(speed);
+    }
+
+    pub fn update(&mut self, dtime: Duration) {
+        self.scene.tick(dtime);
+        self.overlay.update(dtime.as_secs_f32());
+        self.update_rotation(dtime.mul_f32(self.scene.time_scale()));
+        self.update_occlusion();
+    }
+
+    /// The tile under the point, taking the map's rotation into account.
+    pub fn pos_at(&self, point: Vec2) -> Option<Pos> {
+        self.map.pixel_to_pos(point, self.aspect_ratio)
+    }
+
+    pub fn is_rotating(&self) -> bool {
+        self.rotation.is_some()
+    }
+
+    /// Starts turning the isometric map by `quarters` clockwise.
+    ///
+    /// Ignored while the previous rotation or any other animation
+    /// is in progress as the sprites are moved to the absolute points.
+    pub fn rotate(&mut self, quarters: i8) {
+        assert_eq!(self.map.projection(), Projection::Isometric);
+        if self.is_rotating() || self.scene.any_unfinished_actions() {
+            return;
+        }
+        let from = self.map.rotation();
+        let to = (i16::from(from) + i16::from(quarters)).rem_euclid(4) as u8;
+        self.map.set_rotation(to);
+        // The highlights are hidden until the map stops turning.
+        self.overlay.remove_from(&mut self.layers.overlay);
+        self.rotation = Some(Rotation {
+            from: f32::from(from),
+            to: f32::from(from) + f32::from(quarters),
+            time: Duration::from_secs(0),
+        });
+    }
+
+    fn update_rotation(&mut self, dtime: Duration) {
+        let rotation = match &mut self.rotation {
+            Some(rotation) => rotation,
+            None => return,
+        };
+        rotation.time += dtime;
+        let k = (rotation.time.as_secs_f32() / ROTATION_TIME.as_secs_f32()).min(1.0);
+        let k = k * k * (3.0 - 2.0 * k);
+        let quarters = rotation.from + (rotation.to - rotation.from) * k;
+        let is_finished = k >= 1.0;
+        let map = &self.map;
+        let aspect_ratio = self.aspect_ratio;
+        // The lift is the same for any angle, so the sprites' z stays valid.
+        let project = |pos: Pos, levels: i16| {
+            let point = Vec2::new(f32::from(pos.x) + 0.5, f32::from(pos.y) + 0.5);
+            map.project(point, f32::from(levels), quarters, aspect_ratio)
+        };
+        for block in &mut self.blocks {
+            block.sprite.set_pos(project(block.pos, block.level));
+        }
+        for sprites in self.sprites.values_mut() {
+            let point = project(sprites.pos, map.height_at(sprites.pos));
+            sprites.sprite.set_pos(point);
+            sprites.shadow.set_pos(point);
+        }
+        if is_finished {
+            self.rotation = None;
+            let facings: Vec<_> = self
+                .sprites
+                .values()
+                .map(|sprites| (sprites.sprite.clone(), sprites.facing))
+                .collect();
+            for (mut sprite, facing) in facings {
+                self.face(&mut sprite, facing);
+            }
+            let overlay = make_overlay(&mut self.layers, &self.map, self.aspect_ratio);
+            let old = std::mem::replace(&mut self.overlay, overlay);
+            self.overlay.copy_positions(&old);
+        }
+    }
+
+    /// Makes the blocks covering the selected unit or the units
+    /// under the cursor semi-transparent.
+    fn update_occlusion(&mut self) {
+        if self.blocks.is_empty() {
+            return;
+        }
+        // The objects layer's sorting keys, see `SortMode::Y`.
+        let mut focused = Vec::new();
+        for (&id, sprites) in &self.sprites {
+            let is_hovered = matches!(self.cursor, Some(p) if sprites.sprite.contains(p));
+            if sprites.is_hidden {
+                continue;
+            }
+            if self.selected == Some(id) || is_hovered {
+                let depth = sprites.sprite.pos().y + self.layers.objects.z(&sprites.sprite);
+                focused.push((sprites.sprite.rect(), depth));
+            }
+        }
+        for block in &mut self.blocks {
+            let depth = block.sprite.pos().y + block.z;
+            let rect = block.sprite.rect();
+            let is_occluding = focused
+                .iter()
+                .any(|(other, other_depth)| depth > *other_depth && rect.overlaps(other));
+            if is_occluding != block.is_occluding {
+                block.is_occluding = is_occluding;
+                let color = if is_occluding {
+                    BLOCK_COLOR_OCCLUDING
+                } else {
+                    BLOCK_COLOR
+                };
+                block.sprite.set_color(color);
+            }
+        }
+    }
+
+    fn tile_size(&self) -> f32 {
+        let (_, _, _, _, tile_size) = self.map.layout(self.aspect_ratio);
+        tile_size
+    }
+
+    /// The point objects standing on the tile are anchored to.
+    fn pos_to_point(&self, pos: Pos) -> Vec2 {
+        self.map.tile_center(pos, self.aspect_ratio)
+    }
+
+    /// In the isometric projection the objects layer is sorted by the
+    /// ground point, so the objects' z compensates their elevation.
+    fn object_z(&self, pos: Pos, z: f32) -> f32 {
+        self.map.elevation(pos, self.aspect_ratio) + z
+    }
+
+    /// Shadows have to be depth-sorted with the isometric blocks.
+    fn shadows_layer(&self) -> &Layer {
+        match self.map.projection() {
+            Projection::Flat => &self.layers.shadows,
+            Projection::Isometric => &self.layers.objects,
+        }
+    }
+
+    fn add_sprites(&mut self, state: &State) {
+        self.add_tiles();
+        for (id, object) in state.objects() {
+            self.add_object(id, object);
+            if !state.is_known(self.player, id) {
+                self.set_hidden(id, true);
+            }
+        }
+    }
+
+    fn add_tiles(&mut self) {
+        match self.map.projection() {
+            Projection::Flat => self.add_flat_tiles(),
+            Projection::Isometric => self.add_blocks(),
+        }
+    }
+
+    fn add_flat_tiles(&mut self) {
+        let atlas = Atlas::new(assets::get().textures.terrain, TERRAINS.len() as u16, 1);
+        let tile_size = self.tile_size();
+        let (w, h) = (self.map.width().into(), self.map.height().into());
+        let mut tiles = TileLayer::new(atlas, w, h, Vec2::new(tile_size, tile_size));
+        tiles.set_pos(self.map.pos_to_pixel(Pos::new(0, 0), self.aspect_ratio));
+        for pos in self.map.iter() {
+            let tile = terrain_tile(self.map.terrain_at(pos));
+            tiles.set_tile(pos.x.into(), pos.y.into(), Some(tile));
+        }
+        self.layers.bg.add_tile_layer(&tiles);
+        self.terrain_tiles = Some(tiles);
+    }
+
+    /// Builds every isometric tile's column out of blocks, one per level.
+    /// The top block shows the tile's terrain.
+    fn add_blocks(&mut self) {
+        let frames = &assets::get().textures.blocks;
+        let tile_size = self.tile_size();
+        let step = self.map.elevation_step(self.aspect_ratio);
+        // Block texture's top diamond is the upper two thirds of it.
+        let offset = Vec2::new(0.5, 1.0 / 3.0);
+        for pos in self.map.iter() {
+            let ground = self.map.pos_to_pixel(pos, self.aspect_ratio)
+                + Vec2::new(0.0, self.map.tile_size(self.aspect_ratio).y * 0.5);
+            let top = self.map.height_at(pos);
+            for level in 0..=top {
+                let lift = f32::from(level) * step;
+                let mut block = Sprite::from_textures(frames, tile_size * BLOCK_HEIGHT_K);
+                if level == top {
+                    block.set_frame(terrain_frame(self.map.terrain_at(pos)));
+                }
+                block.set_offset(offset);
+                block.set_pos(ground - Vec2::new(0.0, lift));
+                block.set_pick_mode(PickMode::Alpha);
+                let z = lift + (BLOCK_Z + BLOCK_LEVEL_Z * f32::from(level)) * tile_size;
+                self.layers.objects.add(&block);
+                self.layers.objects.set_z(&block, z);
+                self.blocks.push(Block {
+                    sprite: block,
+                    pos,
+                    level,
+                    z,
+                    is_occluding: false,
+                });
+            }
+        }
+    }
+
+    /// Creates a sprite (and its shadow) for an object type described
+    /// in `sprites.ron` and puts it on the tile.
+    ///
+    /// The objects layer is y-sorted so `sub_tile_z` only orders
+    /// the objects sharing a tile (and the isometric blocks).
+    pub fn add_object(&mut self, id: ObjId, object: &Object) {
+        let assets = assets::get();
+        let info = &assets.sprites_info[&object.obj_type];
+        let frames = &assets.sprite_frames[&object.obj_type];
+        let tile_size = self.tile_size();
+        let point = self.pos_to_point(object.pos);
+        let mut sprite = Sprite::from_textures(frames, tile_size * OBJECT_SPRITE_HEIGHT_K);
+        sprite.set_offset(Vec2::new(0.5 - info.offset_x, 1.0 - info.offset_y));
+        sprite.set_pos(point);
+        sprite.set_pick_mode(PickMode::Alpha);
+        // The shadow texture is twice as wide as it's high.
+        let shadow_h = tile_size * info.shadow_size_coefficient * 0.5;
+        let mut shadow = Sprite::from_texture(assets.textures.shadow, shadow_h);
+        shadow.set_centered(true);
+        shadow.set_color(SHADOW_COLOR);
+        shadow.set_pos(point);
+        shadow.set_pick_mode(PickMode::Alpha);
+        let z = info.sub_tile_z * tile_size;
+        let mut shadows = self.shadows_layer().clone();
+        shadows.add(&shadow);
+        if self.map.projection() == Projection::Isometric {
+            shadows.set_z(&shadow, self.object_z(object.pos, SHADOW_Z * tile_size));
+        }
+        self.layers.objects.add(&sprite);
+        let sprite_z = self.object_z(object.pos, z);
+        self.layers.objects.set_z(&sprite, sprite_z);
+        self.sprite_to_obj.insert(sprite.id(), id);
+        self.face(&mut sprite, object.facing);
+        let pos = object.pos;
+        let sprites = ObjectSprites {
+            sprite,
+            shadow,
+            pos,
+            facing: object.facing,
+            z,
+            is_hidden: false,
+            push_time: Duration::from_secs(0),
+        };
+        self.sprites.insert(id, sprites);
+        self.update_color(id);
+    }
+
+    /// How the grid direction looks on the screen: the sprite's mirroring
+    /// and, if the sprite has the back frame, the frame to show.
+    fn screen_facing(&self, sprite: &Sprite, dir: Dir) -> (Option<Facing>, Option<&'static str>) {
+        let (facing, is_back) = match (self.map.projection(), self.map.rotate_dir(dir)) {
+            (Projection::Flat, Dir::North) => (None, true),
+            (Projection::Flat, Dir::South) => (None, false),
+            (_, Dir::East) => (Some(Facing::Right), false),
+            (Projection::Flat, Dir::West) => (Some(Facing::Left), false),
+            // The isometric x axis goes right-down and the y axis goes left-down.
+            (Projection::Isometric, Dir::South) => (Some(Facing::Left), false),
+            (Projection::Isometric, Dir::West) => (Some(Facing::Left), true),
+            (Projection::Isometric, Dir::North) => (Some(Facing::Right), true),
+        };
+        let frame = if !sprite.has_frame(BACK_FRAME) {
+            None
+        } else if is_back {
+            Some(BACK_FRAME)
+        } else {
+            Some("")
+        };
+        (facing, frame)
+    }
+
+    fn face(&self, sprite: &mut Sprite, dir: Dir) {
+        let (facing, frame) = self.screen_facing(sprite, dir);
+        if let Some(facing) = facing {
+            sprite.set_facing(facing);
+        }
+        if let Some(frame) = frame {
+            sprite.set_frame(frame);
+        }
+    }
+
+    fn face_actions(&self, sprite: &Sprite, dir: Dir) -> Vec<Box<dyn Action>> {
+        let (facing, frame) = self.screen_facing(sprite, dir);
+        let mut actions = Vec::new();
+        if let Some(facing) = facing {
+            actions.push(action::SetFacing::new(sprite, facing).boxed());
+        }
+        if let Some(frame) = frame {
+            actions.push(action::SetFrame::new(sprite, frame).boxed());
+        }
+        actions
+    }
+
+    /// Returns the object whose sprite is under the point.
+    pub fn pick(&self, point: Vec2) -> Option<ObjId> {
+        let sprite = self.scene.pick(point)?;
+        self.sprite_to_obj.get(&sprite.id()).copied()
+    }
+
+    pub fn move_mouse(&mut self, point: Vec2) {
+        self.cursor = Some(point);
+        for event in self.scene.move_mouse(point) {
+            let old = self.hovered;
+            self.hovered = match event {
+                HoverEvent::Enter(sprite) => self.sprite_to_obj.get(&sprite.id()).copied(),
+                HoverEvent::Leave(_) => None,
+            };
+            for id in old.into_iter().chain(self.hovered) {
+                self.update_color(id);
+            }
+        }
+    }
+
+    pub fn set_selected(&mut self, id: Option<ObjId>) {
+        let old = std::mem::replace(&mut self.selected, id);
+        for id in old.into_iter().chain(id) {
+            self.update_color(id);
+        }
+    }
+
+    fn object_color(&self, id: ObjId) -> Color {
+        if self.selected == Some(id) {
+            OBJECT_COLOR_SELECTED
+        } else if self.hovered == Some(id) {
+            OBJECT_COLOR_HOVERED
+        } else {
+            OBJECT_COLOR
+        }
+    }
+
+    /// Checks if the object is an enemy hidden in the fog of war.
+    pub fn is_hidden(&self, id: ObjId) -> bool {
+        self.sprites
+            .get(&id)
+            .is_some_and(|sprites| sprites.is_hidden)
+    }
+
+    fn update_color(&mut self, id: ObjId) {
+        let color = self.object_color(id);
+        if let Some(sprites) = self.sprites.get_mut(&id) {
+            if !sprites.is_hidden {
+                sprites.sprite.set_color(color);
+            }
+        }
+    }
+
+    fn set_hidden(&mut self, id: ObjId, is_hidden: bool) {
+        let sprites = self.sprites.get_mut(&id).expect("no such object");
+        sprites.is_hidden = is_hidden;
+        let (color, shadow_color) = if is_hidden {
+            (TRANSPARENT, TRANSPARENT)
+        } else {
+            (OBJECT_COLOR, SHADOW_COLOR)
+        };
+        sprites.sprite.set_color(color);
+        sprites.shadow.set_color(shadow_color);
+        self.update_color(id);
+    }
+
+    /// Shows the event that has been already applied to the state.
+    pub fn show_event(&mut self, event: &Event) {
+        match *event {
+            Event::Moved { id, ref path } => self.show_move(id, path),
+            Event::Pushed { id, ref path } => self.show_push(id, path),
+            Event::Fell { id, .. } => self.show_text(id, Kind::Label("fall")),
+            Event::Bumped { id, dir, .. } => self.show_bump(id, dir),
+            Event::Turned { id, facing } => self.show_turn(id, facing),
+            Event::Damaged {
+                id,
+                amount,
+                is_critical: false,
+            } => self.show_text(id, Kind::Damage(amount)),
+            Event::Damaged {
+                id,
+                amount,
+                is_critical: true,
+            } => {
+                self.show_text(id, Kind::Critical(amount));
+                self.show_text(id, Kind::Label("critical"));
+            }
+            Event::Healed { id, amount } => self.show_text(id, Kind::Heal(amount)),
+            Event::Missed { id } => self.show_text(id, Kind::Miss),
+            Event::Blocked { id } => self.show_text(id, Kind::Label("blocked")),
+            Event::TerrainChanged { pos, terrain } => self.show_terrain(pos, terrain),
+            Event::Died { id } => self.show_death(id),
+            Event::Revealed { player, id } if player == self.player => self.show_vision(id, true),
+            Event::Hidden { player, id } if player == self.player => self.show_vision(id, false),
+            Event::Revealed { .. } | Event::Hidden { .. } => {}
+        }
+    }
+
+    /// Fades the enemy in or out of the fog of war.
+    fn show_vision(&mut self, id: ObjId, is_visible: bool) {
+        let (color, shadow_color) = if is_visible {
+            (self.object_color(id), SHADOW_COLOR)
+        } else {
+            (TRANSPARENT, TRANSPARENT)
+        };
+        let sprites = self.sprites.get_mut(&id).expect("no such object");
+        sprites.is_hidden = !is_visible;
+        if !is_visible && self.hovered == Some(id) {
+            self.hovered = None;
+        }
+        let sprites = &self.sprites[&id];
+        let action = action::Sequence::new(vec![
+            action::Fork::new(
+                action::ChangeColorTo::new(&sprites.shadow, shadow_color, VISION_FADE_TIME).boxed(),
+            )
+            .boxed(),
+            action::ChangeColorTo::new(&sprites.sprite, color, VISION_FADE_TIME).boxed(),
+        ]);
+        self.scene.add_action(action.boxed());
+    }
+
+    fn show_move(&mut self, id: ObjId, path: &[Pos]) {
+        let mut actions = Vec::new();
+        let sprites = &self.sprites[&id];
+        let mut prev = sprites.pos;
+        let mut facing = sprites.facing;
+        for &pos in path {
+            facing = Dir::towards(prev, pos).expect("bad path");
+            actions.extend(self.face_actions(&sprites.sprite, facing));
+            actions.extend(self.step_actions(id, prev, pos, MOVE_STEP_TIME, Easing::Linear));
+            prev = pos;
+        }
+        self.scene
+            .add_action(action::Sequence::new(actions).boxed());
+        let sprites = self.sprites.get_mut(&id).expect("no such object");
+        sprites.pos = prev;
+        sprites.facing = facing;
+    }
+
+    /// Forced movement decelerates at the end and keeps the facing.
+    fn show_push(&mut self, id: ObjId, path: &[Pos]) {
+        let mut actions = Vec::new();
+        let mut prev = self.sprites[&id].pos;
+        for (i, &pos) in path.iter().enumerate() {
+            let easing = if i + 1 == path.len() {
+                Easing::QuadOut
+            } else {
+                Easing::Linear
+            };
+            actions.extend(self.step_actions(id, prev, pos, PUSH_STEP_TIME, easing));
+            prev = pos;
+        }
+        self.scene
+            .add_action(action::Sequence::new(actions).boxed());
+        let sprites = self.sprites.get_mut(&id).expect("no such object");
+        sprites.pos = *path.last().expect("empty path");
+        sprites.push_time = PUSH_STEP_TIME * path.len() as u32;
+    }
+
+    /// Jerks the object towards the obstacle and back.
+    fn show_bump(&mut self, id: ObjId, dir: Dir) {
+        let sprites = self.sprites.get_mut(&id).expect("no such object");
+        let delay = std::mem::replace(&mut sprites.push_time, Duration::from_secs(0));
+        let pos = sprites.pos;
+        let (dx, dy) = dir.delta();
+        let center = Vec2::new(f32::from(pos.x) + 0.5, f32::from(pos.y) + 0.5);
+        let levels = f32::from(self.map.height_at(pos));
+        let quarters = f32::from(self.map.rotation());
+        let project = |point| self.map.project(point, levels, quarters, self.aspect_ratio);
+        let delta =
+            (project(center + Vec2::new(f32::from(dx), f32::from(dy))) - project(center)) * BUMP_K;
+        let sprites = &self.sprites[&id];
+        let mut actions = vec![action::Sleep::new(delay).boxed()];
+        for &(delta, easing) in &[(delta, Easing::QuadOut), (-delta, Easing::QuadIn)] {
+            for sprite in &[&sprites.shadow, &sprites.sprite] {
+                let move_by = action::MoveBy::new(sprite, delta, BUMP_TIME).with_easing(easing);
+                actions.push(action::Fork::new(move_by.boxed()).boxed());
+            }
+            actions.push(action::Sleep::new(BUMP_TIME).boxed());
+        }
+        self.scene
+            .add_action(action::Sequence::new(actions).boxed());
+    }
+
+    /// Moves the object's sprites to the next tile of the path.
+    fn step_actions(
+        &self,
+        id: ObjId,
+        from: Pos,
+        to: Pos,
+        time: Duration,
+        easing: Easing,
+    ) -> Vec<Box<dyn Action>> {
+        let ObjectSprites {
+            sprite, shadow, z, ..
+        } = &self.sprites[&id];
+        let tile_size = self.tile_size();
+        let delta = self.pos_to_point(to) - self.pos_to_point(from);
+        // Keep the sprites' depth in sync with the tile's elevation.
+        let sprite_z = self.object_z(to, *z);
+        let shadow_z = self.object_z(to, SHADOW_Z * tile_size);
+        let is_isometric = self.map.projection() == Projection::Isometric;
+        let mut objects = self.layers.objects.clone();
+        let (sprite_copy, shadow_copy) = (sprite.clone(), shadow.clone());
+        let set_z = move || {
+            objects.set_z(&sprite_copy, sprite_z);
+            if is_isometric {
+                objects.set_z(&shadow_copy, shadow_z);
+            }
+        };
+        let move_shadow = action::MoveBy::new(shadow, delta, time).with_easing(easing);
+        vec![
+            action::Custom::new(Box::new(set_z)).boxed(),
+            action::Fork::new(move_shadow.boxed()).boxed(),
+            action::MoveBy::new(sprite, delta, time)
+                .with_easing(easing)
+                .boxed(),
+        ]
+    }
+
+    fn show_terrain(&mut self, pos: Pos, terrain: Terrain) {
+        self.map.set_terrain_at(pos, terrain);
+        if let Some(tiles) = &mut self.terrain_tiles {
+            tiles.set_tile(pos.x.into(), pos.y.into(), Some(terrain_tile(terrain)));
+        }
+        let top = self.map.height_at(pos);
+        let block = self
+            .blocks
+            .iter_mut()
+            .find(|block| block.pos == pos && block.level == top);
+        if let Some(block) = block {
+            block.sprite.set_frame(terrain_frame(terrain));
+        }
+    }
+
+    fn show_turn(&mut self, id: ObjId, facing: Dir) {
+        let sprites = self.sprites.get_mut(&id).expect("no such object");
+        sprites.facing = facing;
+        let actions = self.face_actions(&self.sprites[&id].sprite, facing);
+        self.scene
+            .add_action(action::Sequence::new(actions).boxed());
+    }
+
+    fn show_text(&mut self, id: ObjId, kind: Kind) {
+        if self.is_hidden(id) {
+            return;
+        }
+        let sprite = &self.sprites[&id].sprite;
+        let point = Vec2::new(sprite.pos().x, sprite.rect().y);
+        self.combat_text.show(&mut self.scene, id, point, kind);
+    }
+
+    fn show_death(&mut self, id: ObjId) {
+        self.show_text(id, Kind::Label("defeated"));
+        self.combat_text.forget(id);
+        let ObjectSprites {
+            sprite,
+            shadow,
+            is_hidden,
+            ..
+        } = self.sprites.remove(&id).expect("no such object");
+        self.sprite_to_obj.remove(&sprite.id());
+        if self.hovered == Some(id) {
+            self.hovered = None;
+        }
+        if is_hidden {
+            // Nothing to fade, the player must not notice the death.
+            let action = action::Sequence::new(vec![
+                action::Hide::new(&self.layers.objects, &sprite).boxed(),
+                action::Hide::new(self.shadows_layer(), &shadow).boxed(),
+            ]);
+            self.scene.add_action(action.boxed());
+            return;
+        }
+        let action = action::Sequence::new(vec![
+            action::Fork::new(
+                action::ChangeColorTo::new(&shadow, TRANSPARENT, DEATH_FADE_TIME).boxed(),
+            )
+            .boxed(),
+            action::ChangeColorTo::new(&sprite, TRANSPARENT, DEATH_FADE_TIME).boxed(),
+            action::Hide::new(&self.layers.objects, &sprite).boxed(),
+            action::Hide::new(self.shadows_layer(), &shadow).boxed(),
+        ]);
+        self.scene.add_action(action.boxed());
+    }
+
+    /// Rebuilds all sprites if the aspect ratio has changed.
+    pub fn resize(&mut self, state: &State, aspect_ratio: f32) {
+        if (self.aspect_ratio - aspect_ratio).abs() < f32::EPSILON {
+            return;
+        }
+        self.aspect_ratio = aspect_ratio;
+        self.layers = Layers::new();
+        let time_scale = self.scene.time_scale();
+        self.scene = Scene::new(self.layers.clone().sorted(self.map.projection()));
+        self.scene.set_time_scale(time_scale);
+        self.combat_text = make_combat_text(&self.layers, &self.map, aspect_ratio);
+        self.overlay = make_overlay(&mut self.layers, &self.map, aspect_ratio);
+        self.sprites.clear();
+        self.sprite_to_obj.clear();
+        self.terrain_tiles = None;
+        self.blocks.clear();
+        // The sprites are created at the final rotation.
+        self.rotation = None;
+        self.hovered = None;
+        self.add_sprites(state);
+    }
+}
+
+/// Order of the tiles in `textures/terrain.png`.
+const TERRAINS: [Terrain; 6] = [
+    Terrain::Plain,
+    Terrain::Grass,
+    Terrain::Fire,
+    Terrain::Ice,
+    Terrain::Water,
+    Terrain::Swamp,
+];
+
+fn terrain_tile(terrain: Terrain) -> u16 {
+    let index = TERRAINS.iter().position(|&t| t == terrain);
+    index.expect("no tile for the terrain") as u16
+}
+
+/// Block frame names, see `assets::MapObjectTextures::blocks`.
+fn terrain_frame(terrain: Terrain) -> &'static str {
+    match terrain {
+        Terrain::Plain => "",
+        Terrain::Grass => "grass",
+        Terrain::Fire => "fire",
+        Terrain::Ice => "ice",
+        Terrain::Water => "water",
+        Terrain::Swamp => "swamp",
+    }
+}
+
+fn make_combat_text(layers: &Layers, map: &Map, aspect_ratio: f32) -> CombatText {
+    let (_, _, _, _, tile_size) = map.layout(aspect_ratio);
+    let font = assets::get().font;
+    CombatText::new(layers.text.clone(), font, tile_size * COMBAT_TEXT_HEIGHT_K)
+}
+
+fn make_overlay(layers: &mut Layers, map: &Map, aspect_ratio: f32) -> Overlay {
+    Overlay::new(&mut layers.overlay, map, aspect_ratio)
+}
@@ -0,0 +1,116 @@
+use std::{collections::HashMap, time::Duration};
+
+use mq::{color::Color, math::Vec2, text::Font};
+use zscene::{action, ActionHandle, Boxed, Layer, Scene, Sprite};
+
+use crate::core::battle::ObjId;
+
+const COLOR_DAMAGE: Color = Color::new(0.9, 0.1, 0.1, 1.0);
+const COLOR_CRITICAL: Color = Color::new(1.0, 0.5, 0.0, 1.0);
+const COLOR_HEAL: Color = Color::new(0.1, 0.7, 0.1, 1.0);
+const COLOR_MISS: Color = Color::new(0.4, 0.4, 0.4, 1.0);
+const COLOR_LABEL: Color = Color::new(0.1, 0.1, 0.1, 1.0);
+
+/// How far a text rises during its life, in line heights.
+const RISE_LINES: f32 = 1.5;
+
+const LIFETIME: Duration = Duration::from_millis(1_200);
+
+#[derive(Debug, Clone, Copy)]
+pub enum Kind {
+    Damage(i32),
+    Critical(i32),
+    Heal(i32),
+    Miss,
+    Label(&'static str),
+}
+
+impl Kind {
+    fn text(self) -> String {
+        match self {
+            Kind::Damage(amount) | Kind::Critical(amount) => format!("-{}", amount),
+            Kind::Heal(amount) => format!("+{}", amount),
+            Kind::Miss => "miss".into(),
+            Kind::Label(label) => label.into(),
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Kind::Damage(_) => COLOR_DAMAGE,
+            Kind::Critical(_) => COLOR_CRITICAL,
+            Kind::Heal(_) => COLOR_HEAL,
+            Kind::Miss => COLOR_MISS,
+            Kind::Label(_) => COLOR_LABEL,
+        }
+    }
+
+    fn height_k(self) -> f32 {
+        match self {
+            Kind::Critical(_) => 1.4,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Spawns rising and fading texts over the objects.
+///
+/// Texts shown over the same object at the same time are stacked
+/// on top of each other.
+#[derive(Debug)]
+pub struct CombatText {
+    layer: Layer,
+    font: Font,
+    line_height: f32,
+
+    /// Slots and actions of the texts that are still shown.
+    active: HashMap<ObjId, Vec<(usize, ActionHandle)>>,
+}
+
+impl CombatText {
+    pub fn new(layer: Layer, font: Font, line_height: f32) -> Self {
+        Self {
+            layer,
+            font,
+            line_height,
+            active: HashMap::new(),
+        }
+    }
+
+    /// Shows the text with its bottom at the point.
+    /// The text takes the lowest slot that is free.
+    pub fn show(&mut self, scene: &mut Scene, id: ObjId, point: Vec2, kind: Kind) {
+        self.active.retain(|_, texts| {
+            texts.retain(|&(_, handle)| !scene.is_finished(handle));
+            !texts.is_empty()
+        });
+        let texts = self.active.entry(id).or_default();
+        let slot = (0..)
+            .find(|&slot| texts.iter().all(|&(used, _)| used != slot))
+            .expect("no free slot");
+        let height = self.line_height * kind.height_k();
+        let mut sprite = Sprite::from_text((&kind.text(), self.font), height);
+        sprite.set_centered(true);
+        sprite.set_color(kind.color());
+        let pos = point - Vec2::new(0.0, self.line_height * (slot as f32 + 0.5));
+        sprite.set_pos(pos);
+        let transparent = Color {
+            a: 0.0,
+            ..kind.color()
+        };
+        let rise = Vec2::new(0.0, -self.line_height * RISE_LINES);
+        let action = action::Sequence::new(vec![
+            action::Show::new(&self.layer, &sprite).boxed(),
+            action::Fork::new(action::MoveBy::new(&sprite, rise, LIFETIME).boxed()).boxed(),
+            action::Sleep::new(LIFETIME / 2).boxed(),
+            action::ChangeColorTo::new(&sprite, transparent, LIFETIME / 2).boxed(),
+            action::Hide::new(&self.layer, &sprite).boxed(),
+        ]);
+        texts.push((slot, scene.add_action(action.boxed())));
+    }
+
+    /// The dead object gets no more texts, the shown ones play to the end.
+    pub fn forget(&mut self, id: ObjId) {
+        self.active.remove(&id);
+    }
+}
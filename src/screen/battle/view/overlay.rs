@@ -0,0 +1,240 @@
+use std::collections::{HashMap, HashSet};
+
+use mq::color::Color;
+use zscene::{Atlas, Grid, Layer, TileLayer};
+
+use crate::{
+    assets,
+    core::map::{Map, Pos, Projection},
+};
+
+/// Layout of `textures/overlay.png`.
+const ATLAS_COLUMNS: u16 = 8;
+const ATLAS_ROWS: u16 = 5;
+const TILE_FILL: u16 = 0;
+/// 16 outlines, one for every combination of the sides (N = 1, E = 2, S = 4, W = 8).
+const TILE_OUTLINE_FIRST: u16 = 1;
+/// 16 path pieces, one for every combination of the connected sides.
+const TILE_PATH_FIRST: u16 = 17;
+/// Arrow heads pointing north, east, south and west.
+const TILE_ARROW_FIRST: u16 = 33;
+
+/// Pulses per second.
+const PULSE_SPEED: f32 = 1.5;
+
+/// The highlights in the order they're drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// Tiles the player has never seen.
+    Unexplored,
+
+    /// Tiles seen before, but not visible now.
+    Fog,
+
+    /// Tiles the selected unit can move to.
+    Movement,
+
+    /// Tiles the selected unit can attack.
+    Attack,
+
+    /// Tiles affected by the action under the cursor.
+    Area,
+
+    /// The path to the tile under the cursor.
+    Path,
+}
+
+const KINDS: [Kind; 6] = [
+    Kind::Unexplored,
+    Kind::Fog,
+    Kind::Movement,
+    Kind::Attack,
+    Kind::Area,
+    Kind::Path,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Shape {
+    Fill,
+    Outline,
+    Path,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Style {
+    shape: Shape,
+    color: Color,
+    is_pulsing: bool,
+}
+
+impl Kind {
+    fn style(self) -> Style {
+        let (shape, color, is_pulsing) = match self {
+            Kind::Unexplored => (Shape::Fill, Color::new(0.0, 0.0, 0.05, 0.6), false),
+            Kind::Fog => (Shape::Fill, Color::new(0.0, 0.0, 0.05, 0.3), false),
+            Kind::Movement => (Shape::Fill, Color::new(0.2, 0.4, 1.0, 0.3), false),
+            Kind::Attack => (Shape::Outline, Color::new(0.9, 0.1, 0.1, 0.8), false),
+            Kind::Area => (Shape::Fill, Color::new(1.0, 0.2, 0.1, 0.5), true),
+            Kind::Path => (Shape::Path, Color::new(1.0, 0.9, 0.4, 1.0), false),
+        };
+        Style {
+            shape,
+            color,
+            is_pulsing,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Highlight {
+    tiles: TileLayer,
+
+    /// Currently shown positions, in the path order for the paths.
+    positions: Vec<Pos>,
+}
+
+/// Fog of war and colored, pulsing, outlined and arrow-path tile highlights.
+///
+/// Every kind is a separate tile layer that's rebuilt only when its
+/// positions change, so it's fine to update it on every mouse move.
+#[derive(Debug)]
+pub struct Overlay {
+    highlights: HashMap<Kind, Highlight>,
+
+    /// Used to place the positions into the rotated grid.
+    map: Map,
+
+    time: f32,
+}
+
+impl Overlay {
+    /// Follows the map's projection, rotation and the tiles' elevation.
+    ///
+    /// The tile layers are laid out in the rotated grid, so a new
+    /// overlay has to be created when the map's rotation changes.
+    pub fn new(layer: &mut Layer, map: &Map, aspect_ratio: f32) -> Self {
+        let texture = assets::get().textures.overlay;
+        let atlas = Atlas::new(texture, ATLAS_COLUMNS, ATLAS_ROWS);
+        let grid = match map.projection() {
+            Projection::Flat => Grid::Square,
+            Projection::Isometric => Grid::Isometric,
+        };
+        let (w, h) = map.rotated_size();
+        let (w, h) = (w.into(), h.into());
+        let size = map.tile_size(aspect_ratio);
+        // The tile at the rotated grid's origin.
+        let origin = map.pos_to_pixel(map.unrotate_pos(Pos::new(0, 0)), aspect_ratio);
+        let mut highlights = HashMap::new();
+        for &kind in &KINDS {
+            let mut tiles = TileLayer::new(atlas, w, h, size).with_grid(grid);
+            tiles.set_pos(origin);
+            tiles.set_color(kind.style().color);
+            for pos in map.iter() {
+                let elevation = map.elevation(pos, aspect_ratio);
+                let rotated = map.rotate_pos(pos);
+                tiles.set_elevation(rotated.x.into(), rotated.y.into(), elevation);
+            }
+            layer.add_tile_layer(&tiles);
+            let positions = Vec::new();
+            highlights.insert(kind, Highlight { tiles, positions });
+        }
+        Self {
+            highlights,
+            map: map.clone(),
+            time: 0.0,
+        }
+    }
+
+    /// Detaches the highlights from the layer they were created in.
+    pub fn remove_from(&self, layer: &mut Layer) {
+        for highlight in self.highlights.values() {
+            layer.remove_tile_layer(&highlight.tiles);
+        }
+    }
+
+    /// Shows the same positions as the other overlay does.
+    pub fn copy_positions(&mut self, other: &Overlay) {
+        for &kind in &KINDS {
+            self.set(kind, other.highlights[&kind].positions.clone());
+        }
+    }
+
+    /// Highlights the positions.
+    ///
+    /// For [Kind::Path] they must go one after another starting
+    /// with the unit's own tile, which isn't highlighted.
+    pub fn set(&mut self, kind: Kind, positions: Vec<Pos>) {
+        let highlight = self.highlights.get_mut(&kind).expect("no such highlight");
+        if highlight.positions == positions {
+            return;
+        }
+        // Rotation keeps the tiles' neighbours, so the shapes can be
+        // built right in the rotated grid.
+        let map = &self.map;
+        let rotated: Vec<Pos> = positions.iter().map(|&pos| map.rotate_pos(pos)).collect();
+        let tiles = &mut highlight.tiles;
+        tiles.fill(None);
+        match kind.style().shape {
+            Shape::Fill => {
+                for pos in &rotated {
+                    tiles.set_tile(pos.x.into(), pos.y.into(), Some(TILE_FILL));
+                }
+            }
+            Shape::Outline => {
+                let set: HashSet<Pos> = rotated.iter().copied().collect();
+                for &pos in &rotated {
+                    let mask = sides_mask(pos, |neighbour| !set.contains(&neighbour));
+                    let tile = TILE_OUTLINE_FIRST + mask;
+                    tiles.set_tile(pos.x.into(), pos.y.into(), Some(tile));
+                }
+            }
+            Shape::Path => {
+                for (i, &pos) in rotated.iter().enumerate().skip(1) {
+                    let prev = rotated[i - 1];
+                    let tile = match rotated.get(i + 1) {
+                        Some(&next) => {
+                            TILE_PATH_FIRST + sides_mask(pos, |n| n == prev || n == next)
+                        }
+                        None => TILE_ARROW_FIRST + side(pos, prev).map_or(0, |s| (s + 2) % 4),
+                    };
+                    tiles.set_tile(pos.x.into(), pos.y.into(), Some(tile));
+                }
+            }
+        }
+        highlight.positions = positions;
+    }
+
+    pub fn clear(&mut self, kind: Kind) {
+        self.set(kind, Vec::new());
+    }
+
+    /// Animates the pulsing highlights.
+    pub fn update(&mut self, dtime: f32) {
+        self.time += dtime;
+        let k = 0.65 + 0.35 * (self.time * PULSE_SPEED * std::f32::consts::PI * 2.0).sin();
+        for (kind, highlight) in &mut self.highlights {
+            let style = kind.style();
+            if !style.is_pulsing || highlight.positions.is_empty() {
+                continue;
+            }
+            let color = Color {
+                a: style.color.a * k,
+                ..style.color
+            };
+            highlight.tiles.set_color(color);
+        }
+    }
+}
+
+/// Index of the side of `pos` that `neighbour` is adjacent to, see [Pos::neighbours].
+fn side(pos: Pos, neighbour: Pos) -> Option<u16> {
+    let index = pos.neighbours().iter().position(|&n| n == neighbour)?;
+    Some(index as u16)
+}
+
+fn sides_mask(pos: Pos, f: impl Fn(Pos) -> bool) -> u16 {
+    let neighbours = pos.neighbours();
+    (0..4)
+        .filter(|&i| f(neighbours[i]))
+        .fold(0, |mask, i| mask | 1 << i)
+}
@@ -1,36 +1,40 @@
-use std::time::Duration;
+use std::{cell::RefCell, rc::Rc, time::Duration};
 
 use mq::math::Vec2;
 use ui::{Gui, Widget};
 
 use crate::AResult;
 use crate::assets;
-use crate::screen::{Screen, StackCommand};
+use crate::core::map::Projection;
+use crate::screen::{self, Screen, StackCommand};
+use crate::settings::Settings;
 use crate::utils;
 
 #[derive(Copy, Clone, Debug)]
 enum Action {
     Exit,
-    Play,
+    Play(Projection),
+    Settings,
+    Story,
 }
 
 fn make_gui() -> AResult<ui::Gui<Action>> {
-    let font = assets::get().font;
-    let mut gui = ui::Gui::new();
+    let theme = assets::get().default_theme();
+    let font = theme.font;
+    let mut gui = ui::Gui::new().with_theme(theme.clone());
     let h = utils::line_heights().large;
-    let space = || Box::new(ui::Spacer::new_vertical(h / 8.0));
     let button = &mut |text, message| -> AResult<_> {
         let text = ui::Drawable::text(text, font);
         let b = ui::Button::new(text, h, gui.sender(), message)?.stretchable(true);
         Ok(Box::new(b))
     };
-    let mut layout = Box::new(ui::VLayout::new().stretchable(true));
-    layout.add(button("campaign", Action::Play)?);
+    let mut layout = Box::new(ui::VLayout::new().stretchable(true).with_spacing(h / 8.0));
+    layout.add(button("campaign", Action::Play(Projection::Flat))?);
+    layout.add(button("isometric", Action::Play(Projection::Isometric))?);
+    layout.add(button("story", Action::Story)?);
+    layout.add(button("settings", Action::Settings)?);
     #[cfg(not(target_arch = "wasm32"))] // can't quit WASM
-    {
-        layout.add(space());
-        layout.add(button("exit", Action::Exit)?);
-    }
+    layout.add(button("exit", Action::Exit)?);
     layout.stretch_to_self();
     let layout = utils::add_offsets_and_bg_big(layout)?;
     let anchor = ui::Anchor(ui::HAnchor::Middle, ui::VAnchor::Middle);
@@ -41,13 +45,34 @@ fn make_gui() -> AResult<ui::Gui<Action>> {
 #[derive(Debug)]
 pub struct MainMenu {
     gui: Gui<Action>,
+    settings: Rc<RefCell<Settings>>,
 }
 
 // TODO: add the game's version to one of the corners
 impl MainMenu {
     pub fn new() -> AResult<Self> {
         let gui = make_gui()?;
-        Ok(Self { gui })
+        let settings = Rc::new(RefCell::new(Settings::default()));
+        Ok(Self { gui, settings })
+    }
+
+    fn handle_action(&mut self, action: Option<Action>) -> AResult<StackCommand> {
+        match action {
+            Some(Action::Play(projection)) => {
+                let screen = screen::BattleScreen::new(projection, &self.settings.borrow())?;
+                Ok(StackCommand::PushScreen(Box::new(screen)))
+            }
+            Some(Action::Settings) => {
+                let screen = screen::SettingsMenu::new(self.settings.clone())?;
+                Ok(StackCommand::PushPopup(Box::new(screen)))
+            }
+            Some(Action::Story) => {
+                let screen = screen::StoryScreen::new()?;
+                Ok(StackCommand::PushPopup(Box::new(screen)))
+            }
+            Some(Action::Exit) => Ok(StackCommand::Pop),
+            None => Ok(StackCommand::None),
+        }
     }
 }
 
@@ -65,17 +90,12 @@ impl Screen for MainMenu {
         let action = self.gui.click(pos);
         println!("Pos: {:?}", pos);
         log::trace!("MainMenu: click: pos={:?}, message={:?}", pos, action);
-        match action {
-            Some(Action::Play) => {
-                /*
-                let screen = screen::Campaign::new()?;
-                Ok(StackCommand::PushScreen(Box::new(screen)))
-                */
-                Ok(StackCommand::None)
-            }
-            Some(Action::Exit) => Ok(StackCommand::Pop),
-            None => Ok(StackCommand::None),
-        }
+        self.handle_action(action)
+    }
+
+    fn press_key(&mut self, key: ui::KeyPress) -> AResult<StackCommand> {
+        let action = self.gui.press_key(key);
+        self.handle_action(action)
     }
 
     fn resize(&mut self, aspect_ratio: f32) {
@@ -86,5 +106,19 @@ impl Screen for MainMenu {
         self.gui.move_mouse(pos);
         Ok(())
     }
-}
 
+    fn release(&mut self, pos: Vec2) -> AResult {
+        self.gui.release(pos);
+        Ok(())
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) -> AResult {
+        self.gui.scroll(pos, delta);
+        Ok(())
+    }
+
+    fn type_char(&mut self, c: char) -> AResult {
+        self.gui.type_char(c);
+        Ok(())
+    }
+}
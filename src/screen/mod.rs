@@ -5,9 +5,14 @@ use mq::math::{Rect, Vec2};
 
 use crate::{utils, AResult};
 
+mod battle;
 mod main_menu;
+mod settings_menu;
+mod story;
 
-pub use main_menu::MainMenu;
+pub use self::{
+    battle::BattleScreen, main_menu::MainMenu, settings_menu::SettingsMenu, story::StoryScreen,
+};
 
 pub const COLOR_SCREEN_BG: Color = Color::new(1.0, 1.0, 1.0, 1.0);
 pub const COLOR_POPUP_BG: Color = Color::new(0.9, 0.9, 0.8, 0.9);
@@ -31,6 +36,22 @@ pub trait Screen: std::fmt::Debug {
     fn move_mouse(&mut self, _pos: Vec2) -> AResult {
         Ok(())
     }
+
+    fn release(&mut self, _pos: Vec2) -> AResult {
+        Ok(())
+    }
+
+    fn scroll(&mut self, _pos: Vec2, _delta: f32) -> AResult {
+        Ok(())
+    }
+
+    fn press_key(&mut self, _key: ui::KeyPress) -> AResult<StackCommand> {
+        Ok(StackCommand::None)
+    }
+
+    fn type_char(&mut self, _c: char) -> AResult {
+        Ok(())
+    }
 }
 
 struct ScreenWithPopups {
@@ -89,6 +110,23 @@ impl ScreenStack {
         self.screen_mut().top_mut().move_mouse(pos)
     }
 
+    pub fn release(&mut self, pos: Vec2) -> AResult {
+        self.screen_mut().top_mut().release(pos)
+    }
+
+    pub fn scroll(&mut self, pos: Vec2, delta: f32) -> AResult {
+        self.screen_mut().top_mut().scroll(pos, delta)
+    }
+
+    pub fn press_key(&mut self, key: ui::KeyPress) -> AResult {
+        let command = self.screen_mut().top_mut().press_key(key)?;
+        self.handle_command(command)
+    }
+
+    pub fn type_char(&mut self, c: char) -> AResult {
+        self.screen_mut().top_mut().type_char(c)
+    }
+
     pub fn resize(&mut self, aspect_ratio: f32) -> AResult {
         for screen in &mut self.screens {
             screen.screen.resize(aspect_ratio);
@@ -141,4 +179,3 @@ impl ScreenStack {
         mq::shapes::draw_rectangle(r.x, r.y, r.w, r.h, COLOR_POPUP_BG);
     }
 }
-
@@ -0,0 +1,146 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use mq::math::Vec2;
+use ui::{Gui, Widget};
+
+use crate::{
+    assets,
+    screen::{Screen, StackCommand},
+    settings::{Difficulty, Settings},
+    utils, AResult,
+};
+
+#[derive(Clone, Debug)]
+enum Message {
+    Volume(f32),
+    AnimationSpeed(f32),
+    Fullscreen(bool),
+    Difficulty(Difficulty),
+    Back,
+}
+
+fn make_gui(settings: &Settings) -> AResult<Gui<Message>> {
+    let theme = assets::get().default_theme();
+    let font = theme.font;
+    let mut gui = Gui::new()
+        .with_theme(theme.clone())
+        .with_cancel_message(Message::Back);
+    let h = utils::line_heights().normal;
+    let w = h * 6.0;
+    let text = |text| ui::Drawable::text(text, font);
+    let label = |s| -> AResult<Box<dyn Widget>> { Ok(Box::new(ui::Label::new(text(s), h)?)) };
+    let align = ui::Anchor(ui::HAnchor::Left, ui::VAnchor::Middle);
+    let mut layout = Box::new(
+        ui::GridLayout::new()
+            .with_spacing(h / 4.0)
+            .with_align(align),
+    );
+    layout.add(label("volume")?, 0, 0);
+    let volume = ui::Slider::new(w, h, gui.sender(), Message::Volume)
+        .with_step(0.1)
+        .with_value(settings.volume);
+    layout.add(Box::new(volume), 0, 1);
+    layout.add(label("animation speed")?, 1, 0);
+    let speed = ui::Slider::new(w, h, gui.sender(), Message::AnimationSpeed)
+        .with_range(0.5, 2.0)
+        .with_value(settings.animation_speed);
+    layout.add(Box::new(speed), 1, 1);
+    layout.add(label("difficulty")?, 2, 0);
+    let options = Difficulty::ALL
+        .iter()
+        .map(|&difficulty| (difficulty.name().to_string(), difficulty))
+        .collect();
+    let index = Difficulty::ALL
+        .iter()
+        .position(|&difficulty| difficulty == settings.difficulty)
+        .unwrap_or(0);
+    let sender = gui.sender();
+    let difficulty = ui::Selector::new(font, h, options, index, sender, Message::Difficulty)?;
+    layout.add(Box::new(difficulty), 2, 1);
+    let is_fullscreen = settings.is_fullscreen;
+    let fullscreen_text = text("fullscreen");
+    let fullscreen = ui::Checkbox::new(
+        fullscreen_text,
+        h,
+        is_fullscreen,
+        gui.sender(),
+        Message::Fullscreen,
+    );
+    layout.add_spanned(Box::new(fullscreen), 3, 0, 1, 2);
+    let back = ui::Button::new(text("back"), h, gui.sender(), Message::Back)?.stretchable(true);
+    layout.add_spanned(Box::new(back), 4, 0, 1, 2);
+    let layout = utils::add_offsets_and_bg_big(layout)?;
+    let anchor = ui::Anchor(ui::HAnchor::Middle, ui::VAnchor::Middle);
+    gui.add(&ui::pack(layout), anchor);
+    Ok(gui)
+}
+
+/// A popup that edits the shared settings right away.
+#[derive(Debug)]
+pub struct SettingsMenu {
+    gui: Gui<Message>,
+    settings: Rc<RefCell<Settings>>,
+}
+
+impl SettingsMenu {
+    pub fn new(settings: Rc<RefCell<Settings>>) -> AResult<Self> {
+        let gui = make_gui(&settings.borrow())?;
+        Ok(Self { gui, settings })
+    }
+
+    /// Handles the message and the ones sent by the dragged sliders.
+    fn handle_messages(&mut self, message: Option<Message>) -> StackCommand {
+        let messages: Vec<_> = message.into_iter().chain(self.gui.messages()).collect();
+        let mut command = StackCommand::None;
+        for message in messages {
+            log::trace!("SettingsMenu: message={:?}", message);
+            let mut settings = self.settings.borrow_mut();
+            match message {
+                Message::Volume(value) => settings.volume = value,
+                Message::AnimationSpeed(value) => settings.animation_speed = value,
+                Message::Fullscreen(value) => {
+                    settings.is_fullscreen = value;
+                    utils::set_fullscreen(value);
+                }
+                Message::Difficulty(value) => settings.difficulty = value,
+                Message::Back => command = StackCommand::Pop,
+            }
+        }
+        command
+    }
+}
+
+impl Screen for SettingsMenu {
+    fn update(&mut self, _: Duration) -> AResult<StackCommand> {
+        Ok(self.handle_messages(None))
+    }
+
+    fn draw(&self) -> AResult {
+        self.gui.draw();
+        Ok(())
+    }
+
+    fn click(&mut self, pos: Vec2) -> AResult<StackCommand> {
+        let message = self.gui.click(pos);
+        Ok(self.handle_messages(message))
+    }
+
+    fn resize(&mut self, aspect_ratio: f32) {
+        self.gui.resize_if_needed(aspect_ratio);
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) -> AResult {
+        self.gui.move_mouse(pos);
+        Ok(())
+    }
+
+    fn release(&mut self, pos: Vec2) -> AResult {
+        self.gui.release(pos);
+        Ok(())
+    }
+
+    fn press_key(&mut self, key: ui::KeyPress) -> AResult<StackCommand> {
+        let message = self.gui.press_key(key);
+        Ok(self.handle_messages(message))
+    }
+}
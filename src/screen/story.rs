@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use mq::{
+    color::Color,
+    math::{Rect, Vec2},
+};
+use ui::{Gui, Widget};
+
+use crate::{
+    assets,
+    screen::{Screen, StackCommand},
+    utils, AResult,
+};
+
+const STORY: &str = include_str!("../../story/main.md");
+
+const COLOR_TITLE: Color = Color::new(0.5, 0.1, 0.0, 1.0);
+
+#[derive(Clone, Copy, Debug)]
+enum Message {
+    Back,
+}
+
+/// Turns the `[Title]` lines into colored paragraphs
+/// and joins the hard-wrapped lines of the rest.
+fn story_spans(story: &str) -> Vec<ui::Span> {
+    let mut spans = Vec::new();
+    for paragraph in story.split("\n\n") {
+        let lines: Vec<&str> = paragraph.lines().map(str::trim).collect();
+        if lines.is_empty() || lines.iter().all(|line| line.is_empty()) {
+            continue;
+        }
+        let mut text = String::new();
+        for line in lines {
+            if line.starts_with('[') && line.ends_with(']') {
+                let title = format!("{}\n", line.trim_matches(|c| c == '[' || c == ']'));
+                spans.push(ui::Span::text(title).with_color(COLOR_TITLE).bold());
+            } else {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(line);
+            }
+        }
+        text.push_str("\n\n");
+        spans.push(ui::Span::text(text));
+    }
+    spans
+}
+
+fn make_gui() -> AResult<Gui<Message>> {
+    let theme = assets::get().default_theme();
+    let font = theme.font;
+    let mut gui = Gui::new()
+        .with_theme(theme.clone())
+        .with_cancel_message(Message::Back);
+    let h = utils::line_heights().small;
+    let w = 1.2;
+    let mut layout = Box::new(ui::VLayout::new());
+    let mut text = ui::RichText::new(story_spans(STORY), font, h, w);
+    if let Some(bold_font) = theme.bold_font {
+        text = text.with_bold_font(bold_font);
+    }
+    let rect = Rect::new(0.0, 0.0, w + ui::SCROLLBAR_WIDTH, 1.2);
+    layout.add(Box::new(ui::ScrollArea::new(Box::new(text), rect)));
+    let back = ui::Drawable::text("back", font);
+    let h_button = utils::line_heights().normal;
+    let back = ui::Button::new(back, h_button, gui.sender(), Message::Back)?.stretchable(true);
+    layout.add(Box::new(back));
+    layout.stretch_to_self();
+    let layout = utils::add_offsets_and_bg_big(layout)?;
+    let anchor = ui::Anchor(ui::HAnchor::Middle, ui::VAnchor::Middle);
+    gui.add(&ui::pack(layout), anchor);
+    Ok(gui)
+}
+
+/// A popup with the scrollable story text.
+#[derive(Debug)]
+pub struct StoryScreen {
+    gui: Gui<Message>,
+}
+
+impl StoryScreen {
+    pub fn new() -> AResult<Self> {
+        let gui = make_gui()?;
+        Ok(Self { gui })
+    }
+
+    fn handle_message(&mut self, message: Option<Message>) -> StackCommand {
+        match message {
+            Some(Message::Back) => StackCommand::Pop,
+            None => StackCommand::None,
+        }
+    }
+}
+
+impl Screen for StoryScreen {
+    fn update(&mut self, _: Duration) -> AResult<StackCommand> {
+        Ok(StackCommand::None)
+    }
+
+    fn draw(&self) -> AResult {
+        self.gui.draw();
+        Ok(())
+    }
+
+    fn click(&mut self, pos: Vec2) -> AResult<StackCommand> {
+        let message = self.gui.click(pos);
+        Ok(self.handle_message(message))
+    }
+
+    fn resize(&mut self, aspect_ratio: f32) {
+        self.gui.resize_if_needed(aspect_ratio);
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) -> AResult {
+        self.gui.move_mouse(pos);
+        Ok(())
+    }
+
+    fn release(&mut self, pos: Vec2) -> AResult {
+        self.gui.release(pos);
+        Ok(())
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) -> AResult {
+        self.gui.scroll(pos, delta);
+        Ok(())
+    }
+
+    fn press_key(&mut self, key: ui::KeyPress) -> AResult<StackCommand> {
+        let message = self.gui.press_key(key);
+        Ok(self.handle_message(message))
+    }
+}
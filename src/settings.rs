@@ -0,0 +1,43 @@
+/// Player's preferences shared by the screens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// Sound volume, from `0.0` to `1.0`.
+    pub volume: f32,
+
+    /// Multiplier of the battle animations' speed.
+    pub animation_speed: f32,
+
+    pub is_fullscreen: bool,
+
+    pub difficulty: Difficulty,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            volume: 0.8,
+            animation_speed: 1.0,
+            is_fullscreen: false,
+            difficulty: Difficulty::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
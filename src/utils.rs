@@ -24,7 +24,7 @@ pub const OFFSET_SMALL: f32 = 0.02;
 pub const OFFSET_BIG: f32 = 0.04;
 
 pub fn add_bg(w: Box<dyn ui::Widget>) -> AResult<ui::LayersLayout> {
-    let bg = ui::ColoredRect::new(ui::SPRITE_COLOR_BG, w.rect()).stretchable(true);
+    let bg = ui::Panel::new(w.rect()).stretchable(true);
     let mut layers = ui::LayersLayout::new();
     layers.add(Box::new(bg));
     layers.add(w);
@@ -32,22 +32,9 @@ pub fn add_bg(w: Box<dyn ui::Widget>) -> AResult<ui::LayersLayout> {
 }
 
 pub fn add_offsets(w: Box<dyn ui::Widget>, offset: f32) -> Box<dyn ui::Widget> {
-    let spacer = || {
-        ui::Spacer::new(Rect {
-            w: offset,
-            h: offset,
-            ..Default::default()
-        })
-    };
-    let mut layout_h = ui::HLayout::new().stretchable(true);
-    layout_h.add(Box::new(spacer()));
-    layout_h.add(w);
-    layout_h.add(Box::new(spacer()));
-    let mut layout_v = ui::VLayout::new().stretchable(true);
-    layout_v.add(Box::new(spacer()));
-    layout_v.add(Box::new(layout_h));
-    layout_v.add(Box::new(spacer()));
-    Box::new(layout_v)
+    let mut layout = ui::VLayout::new().stretchable(true).with_padding(offset);
+    layout.add(w);
+    Box::new(layout)
 }
 
 pub fn add_offsets_and_bg(w: Box<dyn ui::Widget>, offset: f32) -> AResult<ui::LayersLayout> {
@@ -58,6 +45,13 @@ pub fn add_offsets_and_bg_big(w: Box<dyn ui::Widget>) -> AResult<ui::LayersLayou
     add_offsets_and_bg(w, OFFSET_BIG)
 }
 
+pub fn set_fullscreen(value: bool) {
+    // Macroquad doesn't wrap this miniquad's function.
+    unsafe { window::get_internal_gl() }
+        .quad_context
+        .set_fullscreen(value);
+}
+
 pub fn aspect_ratio() -> f32 {
     window::screen_width() / window::screen_height()
 }
@@ -0,0 +1,90 @@
+use mq::{color::WHITE, texture::Texture2D};
+use zgui as ui;
+
+mod common;
+
+const SIZE: u16 = 12;
+
+#[derive(Clone, Copy, Debug)]
+enum Message {
+    Command,
+}
+
+/// A frame with a 2px dark border around the fill.
+fn make_frame_texture(fill: [u8; 4]) -> Texture2D {
+    let mut bytes = Vec::new();
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let distance = x.min(y).min(SIZE - 1 - x).min(SIZE - 1 - y);
+            let color = if distance < 2 {
+                [40, 40, 40, 255]
+            } else {
+                fill
+            };
+            bytes.extend_from_slice(&color);
+        }
+    }
+    Texture2D::from_rgba8(SIZE, SIZE, &bytes)
+}
+
+fn nine_slice(fill: [u8; 4]) -> ui::NineSlice {
+    ui::NineSlice {
+        texture: make_frame_texture(fill),
+        margin: 3.0,
+        corner: 0.03,
+    }
+}
+
+fn make_theme() -> ui::Theme {
+    ui::Theme {
+        panel: Some(nine_slice([200, 200, 170, 255])),
+        button: Some(ui::ButtonTextures {
+            normal: nine_slice([150, 180, 220, 255]),
+            hovered: Some(nine_slice([180, 210, 250, 255])),
+            pressed: Some(nine_slice([100, 130, 170, 255])),
+            disabled: None,
+        }),
+        ..ui::Theme::default()
+    }
+}
+
+fn make_gui(font: mq::text::Font) -> ui::Result<ui::Gui<Message>> {
+    let mut gui = ui::Gui::new().with_theme(make_theme());
+    let anchor = ui::Anchor(ui::HAnchor::Middle, ui::VAnchor::Middle);
+    let mut layout = ui::VLayout::new();
+    let text = ui::Drawable::text("Label", font);
+    layout.add(Box::new(ui::Label::new_with_bg(text, 0.2)?));
+    layout.add(Box::new(ui::Spacer::new_vertical(0.05)));
+    let text = ui::Drawable::text("Button", font);
+    let button = ui::Button::new(text, 0.2, gui.sender(), Message::Command)?;
+    layout.add(Box::new(button));
+    gui.add(&ui::pack(layout), anchor);
+    Ok(gui)
+}
+
+#[mq::main("ZGui: Nine-Slice Demo")]
+#[macroquad(crate_rename = "mq")]
+async fn main() {
+    let assets = common::Assets::load().await.expect("Can't load assets");
+    let mut gui = make_gui(assets.font).expect("Can't create the gui");
+    loop {
+        // Update the camera and the GUI.
+        let aspect_ratio = common::aspect_ratio();
+        let camera = common::make_and_set_camera(aspect_ratio);
+        gui.resize_if_needed(aspect_ratio);
+        // Handle cursor updates.
+        let pos = common::get_world_mouse_pos(&camera);
+        gui.move_mouse(pos);
+        if mq::input::is_mouse_button_pressed(mq::input::MouseButton::Left) {
+            let message = gui.click(pos);
+            println!("{:?}", message);
+        }
+        if mq::input::is_mouse_button_released(mq::input::MouseButton::Left) {
+            gui.release(pos);
+        }
+        // Draw the GUI.
+        mq::window::clear_background(WHITE);
+        gui.draw();
+        mq::window::next_frame().await;
+    }
+}
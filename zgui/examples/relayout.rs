@@ -0,0 +1,136 @@
+use std::{cell::RefCell, rc::Rc};
+
+use mq::color::WHITE;
+use zgui as ui;
+
+mod common;
+
+#[derive(Clone, Copy, Debug)]
+enum Message {
+    Add,
+    Remove,
+    Rename,
+}
+
+fn make_label(text: &str, font: mq::text::Font) -> ui::Result<Box<dyn ui::Widget>> {
+    let label = ui::Label::new_with_bg(ui::Drawable::text(text, font), 0.1)?;
+    Ok(Box::new(label.stretchable(true)))
+}
+
+struct State {
+    font: mq::text::Font,
+    gui: ui::Gui<Message>,
+    list: Rc<RefCell<ui::VLayout>>,
+    title: Rc<RefCell<ui::Label>>,
+    items: Vec<ui::Handle>,
+    renames_count: usize,
+}
+
+impl State {
+    fn new(font: mq::text::Font) -> ui::Result<Self> {
+        let mut gui = ui::Gui::new();
+        let title = Rc::new(RefCell::new(ui::Label::new(
+            ui::Drawable::text("Items: 0", font),
+            0.1,
+        )?));
+        let mut list = ui::VLayout::new()
+            .stretchable(true)
+            .with_spacing(0.01)
+            .with_align(ui::HAnchor::Middle);
+        // The layout keeps a shared label, so its text can be changed later.
+        list.add(Box::new(title.clone()));
+        let list = Rc::new(RefCell::new(list));
+        gui.add(
+            &(list.clone() as ui::RcWidget),
+            ui::Anchor(ui::HAnchor::Middle, ui::VAnchor::Middle),
+        );
+        let mut buttons = ui::HLayout::new().with_spacing(0.02);
+        for &(text, message) in &[
+            ("Add", Message::Add),
+            ("Remove", Message::Remove),
+            ("Rename", Message::Rename),
+        ] {
+            let text = ui::Drawable::text(text, font);
+            buttons.add(Box::new(ui::Button::new(text, 0.1, gui.sender(), message)?));
+        }
+        gui.add(
+            &ui::pack(buttons),
+            ui::Anchor(ui::HAnchor::Right, ui::VAnchor::Bottom),
+        );
+        Ok(Self {
+            font,
+            gui,
+            list,
+            title,
+            items: Vec::new(),
+            renames_count: 0,
+        })
+    }
+
+    /// The new items are inserted on top of the older ones.
+    fn add_item(&mut self) -> ui::Result {
+        let text = format!("Item #{}", self.items.len() + 1);
+        let label = make_label(&text, self.font)?;
+        let mut list = self.list.borrow_mut();
+        let handle = match self.items.last() {
+            Some(&newest) => list.insert_before(newest, label)?,
+            None => list.add(label),
+        };
+        self.items.push(handle);
+        Ok(())
+    }
+
+    fn remove_item(&mut self) -> ui::Result {
+        if let Some(handle) = self.items.pop() {
+            self.list.borrow_mut().remove(handle)?;
+        }
+        Ok(())
+    }
+
+    fn rename_item(&mut self) -> ui::Result {
+        if let Some(&handle) = self.items.last() {
+            self.renames_count += 1;
+            let text = format!("Renamed {} times", self.renames_count);
+            let label = make_label(&text, self.font)?;
+            self.list.borrow_mut().replace(handle, label)?;
+        }
+        Ok(())
+    }
+
+    fn handle_message(&mut self, message: Message) -> ui::Result {
+        match message {
+            Message::Add => self.add_item()?,
+            Message::Remove => self.remove_item()?,
+            Message::Rename => self.rename_item()?,
+        }
+        let text = format!("Items: {}", self.items.len());
+        self.title.borrow_mut().set_text(text)
+    }
+}
+
+#[mq::main("ZGui: Relayout Demo")]
+#[macroquad(crate_rename = "mq")]
+async fn main() {
+    let assets = common::Assets::load().await.expect("Can't load assets");
+    let mut state = State::new(assets.font).expect("Can't create the game state");
+    loop {
+        // Update the camera and the GUI, the changed widgets are laid out here.
+        let aspect_ratio = common::aspect_ratio();
+        let camera = common::make_and_set_camera(aspect_ratio);
+        state.gui.resize_if_needed(aspect_ratio);
+        // Handle cursor updates.
+        let pos = common::get_world_mouse_pos(&camera);
+        state.gui.move_mouse(pos);
+        if mq::input::is_mouse_button_pressed(mq::input::MouseButton::Left) {
+            if let Some(message) = state.gui.click(pos) {
+                state
+                    .handle_message(message)
+                    .expect("Can't update the list");
+            }
+        }
+        // Draw the GUI.
+        mq::window::clear_background(WHITE);
+        state.gui.draw();
+        mq::window::next_frame().await;
+    }
+}
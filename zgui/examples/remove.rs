@@ -39,7 +39,7 @@ impl State {
     fn remove_label(&mut self) {
         println!("Removing...");
         if let Some(ref label) = self.label.take() {
-            self.gui.remove(label);
+            self.gui.remove(label).expect("Can't remove the label");
         }
         println!("Removed.");
     }
@@ -0,0 +1,57 @@
+use mq::{color::WHITE, math::Rect};
+use zgui::{self as ui, Widget};
+
+mod common;
+
+#[derive(Clone, Copy, Debug)]
+enum Message {
+    Item(usize),
+}
+
+fn make_gui(font: mq::text::Font) -> ui::Result<ui::Gui<Message>> {
+    let mut gui = ui::Gui::new();
+    let anchor = ui::Anchor(ui::HAnchor::Middle, ui::VAnchor::Middle);
+    let mut layout = ui::VLayout::new().stretchable(true);
+    for i in 0..20 {
+        let text = ui::Drawable::text(format!("Item #{}", i), font);
+        let button = ui::Button::new(text, 0.1, gui.sender(), Message::Item(i))?;
+        layout.add(Box::new(button.stretchable(true)));
+    }
+    layout.stretch_to_self();
+    let rect = Rect::new(0.0, 0.0, layout.rect().w + ui::SCROLLBAR_WIDTH, 0.6);
+    let area = ui::ScrollArea::new(Box::new(layout), rect);
+    gui.add(&ui::pack(area), anchor);
+    Ok(gui)
+}
+
+#[mq::main("ZGui: Scroll Area Demo")]
+#[macroquad(crate_rename = "mq")]
+async fn main() {
+    let assets = common::Assets::load().await.expect("Can't load assets");
+    let mut gui = make_gui(assets.font).expect("Can't create the gui");
+    loop {
+        // Update the camera and the GUI.
+        let aspect_ratio = common::aspect_ratio();
+        let camera = common::make_and_set_camera(aspect_ratio);
+        gui.resize_if_needed(aspect_ratio);
+        // Handle cursor updates.
+        let pos = common::get_world_mouse_pos(&camera);
+        gui.move_mouse(pos);
+        if mq::input::is_mouse_button_pressed(mq::input::MouseButton::Left) {
+            if let Some(Message::Item(i)) = gui.click(pos) {
+                println!("Clicked item #{}", i);
+            }
+        }
+        if mq::input::is_mouse_button_released(mq::input::MouseButton::Left) {
+            gui.release(pos);
+        }
+        let (_, wheel) = mq::input::mouse_wheel();
+        if wheel != 0.0 {
+            gui.scroll(pos, wheel.signum());
+        }
+        // Draw the GUI.
+        mq::window::clear_background(WHITE);
+        gui.draw();
+        mq::window::next_frame().await;
+    }
+}
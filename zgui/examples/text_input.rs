@@ -0,0 +1,80 @@
+use mq::{
+    color::WHITE,
+    input::{self, KeyCode},
+};
+use zgui as ui;
+
+mod common;
+
+#[derive(Clone, Debug)]
+enum Message {
+    Submit(String),
+    Cancel,
+}
+
+const KEYS: [KeyCode; 9] = [
+    KeyCode::Tab,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::Backspace,
+    KeyCode::Delete,
+    KeyCode::Enter,
+    KeyCode::Escape,
+];
+
+fn make_gui(font: mq::text::Font) -> ui::Result<ui::Gui<Message>> {
+    let mut gui = ui::Gui::new().with_cancel_message(Message::Cancel);
+    let anchor = ui::Anchor(ui::HAnchor::Middle, ui::VAnchor::Middle);
+    let input = ui::TextInput::new(font, 0.8, 0.12, gui.sender(), Message::Submit)
+        .with_placeholder("Seed (digits)")
+        .with_max_len(12)
+        .with_validator(|text| text.chars().all(|c| c.is_ascii_digit()));
+    gui.add(&ui::pack(input), anchor);
+    Ok(gui)
+}
+
+#[mq::main("ZGui: Text Input Demo")]
+#[macroquad(crate_rename = "mq")]
+async fn main() {
+    let assets = common::Assets::load().await.expect("Can't load assets");
+    let mut gui = make_gui(assets.font).expect("Can't create the gui");
+    loop {
+        // Update the camera and the GUI.
+        let aspect_ratio = common::aspect_ratio();
+        let camera = common::make_and_set_camera(aspect_ratio);
+        gui.resize_if_needed(aspect_ratio);
+        // Handle cursor updates.
+        let pos = common::get_world_mouse_pos(&camera);
+        gui.move_mouse(pos);
+        if input::is_mouse_button_pressed(input::MouseButton::Left) {
+            gui.click(pos);
+        }
+        if input::is_mouse_button_released(input::MouseButton::Left) {
+            gui.release(pos);
+        }
+        // Handle the keyboard.
+        let shift = input::is_key_down(KeyCode::LeftShift);
+        for &code in &KEYS {
+            if input::is_key_pressed(code) {
+                match gui.press_key(ui::KeyPress { code, shift }) {
+                    Some(Message::Submit(text)) => println!("Submitted: {}", text),
+                    Some(Message::Cancel) => println!("Canceled"),
+                    None => {}
+                }
+            }
+        }
+        let mut chars = Vec::new();
+        while let Some(c) = input::get_char_pressed() {
+            chars.push(c);
+        }
+        for &c in chars.iter().rev() {
+            gui.type_char(c);
+        }
+        // Draw the GUI.
+        mq::window::clear_background(WHITE);
+        gui.draw();
+        mq::window::next_frame().await;
+    }
+}
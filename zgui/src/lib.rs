@@ -11,21 +11,37 @@ use std::{
 use log::{info, trace};
 use mq::{
     camera::{set_camera, Camera2D},
-    color::Color,
+    color::{Color, WHITE},
+    input::KeyCode,
     math::{Rect, Vec2},
     shapes,
     text::{camera_font_scale, draw_text_ex, measure_text, Font, TextParams},
     texture::{draw_texture_ex, DrawTextureParams, Texture2D},
-    window,
+    time::get_time,
+    window::{self, get_internal_gl},
 };
 
-pub const SPRITE_COLOR: Color = Color::new(0.0, 0.0, 0.0, 1.0);
-pub const SPRITE_COLOR_INACTIVE: Color = Color::new(0.4, 0.4, 0.4, 0.5);
-pub const SPRITE_COLOR_BG: Color = Color::new(0.8, 0.8, 0.8, 0.5);
-pub const SPRITE_COLOR_BG_HIGHLIGHTED: Color = Color::new(0.9, 0.9, 0.9, 1.0);
-pub const SPRITE_COLOR_BUTTON_BORDER: Color = Color::new(0.0, 0.0, 0.0, 0.9);
+/// Width of the [ScrollArea]'s scrollbar.
+pub const SCROLLBAR_WIDTH: f32 = 0.03;
 
-// TODO: Add ScrollArea widget
+/// Part of the visible height scrolled by one mouse wheel notch.
+const SCROLL_STEP_K: f32 = 0.15;
+
+/// Caret's width as a part of the [TextInput]'s height.
+const CARET_WIDTH_K: f32 = 0.05;
+
+/// Seconds the cursor has to stay over a widget to show its [Tooltip].
+const TOOLTIP_DELAY: f64 = 0.5;
+
+/// Distance between the cursor and the tooltip's panel.
+const TOOLTIP_OFFSET: f32 = 0.04;
+
+const TOOLTIP_PADDING: f32 = 0.02;
+
+thread_local! {
+    /// Nested clip rects, the last one is applied.
+    static CLIPS: RefCell<Vec<Rect>> = const { RefCell::new(Vec::new()) };
+}
 
 pub type Result<T = ()> = std::result::Result<T, Error>;
 
@@ -34,6 +50,10 @@ pub enum Error {
     BadBorderCoefficient,
     BadContentCoefficient,
     NoDimensions,
+    NoOptions,
+    NoSuchWidget,
+    NotText,
+    BadPadding,
 }
 
 impl fmt::Display for Error {
@@ -42,6 +62,10 @@ impl fmt::Display for Error {
             Error::BadBorderCoefficient => write!(f, "Border size is too large"),
             Error::BadContentCoefficient => write!(f, "Content size is too large"),
             Error::NoDimensions => write!(f, "The drawable has no dimensions"),
+            Error::NoOptions => write!(f, "The selector has no options"),
+            Error::NoSuchWidget => write!(f, "The widget isn't found"),
+            Error::NotText => write!(f, "The drawable isn't a text"),
+            Error::BadPadding => write!(f, "Padding is negative or too large"),
         }
     }
 }
@@ -49,13 +73,124 @@ impl fmt::Display for Error {
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
-            Error::BadBorderCoefficient | Error::BadContentCoefficient | Error::NoDimensions => {
-                None
-            }
+            Error::BadBorderCoefficient
+            | Error::BadContentCoefficient
+            | Error::NoDimensions
+            | Error::NoOptions
+            | Error::NoSuchWidget
+            | Error::NotText
+            | Error::BadPadding => None,
+        }
+    }
+}
+
+/// Colors of the widgets' parts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub text: Color,
+    pub text_inactive: Color,
+    pub bg: Color,
+    pub bg_highlighted: Color,
+    pub border: Color,
+    pub focus: Color,
+    pub error: Color,
+    pub selection: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            text: Color::new(0.0, 0.0, 0.0, 1.0),
+            text_inactive: Color::new(0.4, 0.4, 0.4, 0.5),
+            bg: Color::new(0.8, 0.8, 0.8, 0.5),
+            bg_highlighted: Color::new(0.9, 0.9, 0.9, 1.0),
+            border: Color::new(0.0, 0.0, 0.0, 0.9),
+            focus: Color::new(0.9, 0.5, 0.0, 1.0),
+            error: Color::new(0.8, 0.0, 0.0, 1.0),
+            selection: Color::new(0.3, 0.5, 1.0, 0.4),
+        }
+    }
+}
+
+/// The look of the widgets, applied with [Gui::set_theme] or [Themed].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub colors: Palette,
+
+    /// Overrides the widgets' own thickness of the borders,
+    /// see [ButtonParam::border_k].
+    pub border_k: Option<f32>,
+
+    /// Distance between the anchored widgets and the screen's edges.
+    pub padding: f32,
+
+    /// The fonts aren't changed in the existing widgets,
+    /// they're for making the text drawables.
+    pub font: Font,
+
+    /// For the [RichText]'s bold spans.
+    pub bold_font: Option<Font>,
+
+    /// Replaces the solid backgrounds of the [Panel]s and the labels.
+    pub panel: Option<NineSlice>,
+
+    /// Replaces the [Button]s' solid backgrounds and borders.
+    pub button: Option<ButtonTextures>,
+}
+
+impl Theme {
+    /// Checks the border thickness override against the default [ButtonParam].
+    pub fn check(&self) -> Result {
+        if let Some(border_k) = self.border_k {
+            let param = ButtonParam {
+                border_k,
+                ..ButtonParam::default()
+            };
+            param.check()?;
+        }
+        if !(0.0..1.0).contains(&self.padding) {
+            return Err(Error::BadPadding);
+        }
+        Ok(())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            colors: Palette::default(),
+            border_k: None,
+            padding: 0.02,
+            font: Font::default(),
+            bold_font: None,
+            panel: None,
+            button: None,
         }
     }
 }
 
+/// A texture split into nine parts: the corners keep their size
+/// while the edges and the center are stretched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NineSlice {
+    pub texture: Texture2D,
+
+    /// Size of the texture's corners in pixels.
+    pub margin: f32,
+
+    /// Size of the drawn corners.
+    pub corner: f32,
+}
+
+/// Backgrounds of the [Button]'s states, the normal one is used for the missing ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ButtonTextures {
+    pub normal: NineSlice,
+    pub hovered: Option<NineSlice>,
+    pub pressed: Option<NineSlice>,
+    pub disabled: Option<NineSlice>,
+}
+
 pub fn pack<W: Widget + 'static>(widget: W) -> RcWidget {
     Rc::new(RefCell::new(widget))
 }
@@ -75,6 +210,10 @@ pub enum Drawable {
         rect: Rect,
         thickness: f32,
     },
+    NineSlice {
+        nine_slice: NineSlice,
+        rect: Rect,
+    },
 }
 
 impl Drawable {
@@ -108,6 +247,7 @@ impl Drawable {
             }
             Drawable::SolidRect { rect, .. } => rect,
             Drawable::LinesRect { rect, .. } => rect,
+            Drawable::NineSlice { rect, .. } => rect,
         }
     }
 }
@@ -132,7 +272,7 @@ impl Sprite {
             basic_scale,
             pos: Vec2::new(0.0, 0.0),
             scale: Vec2::new(basic_scale, basic_scale),
-            color: SPRITE_COLOR,
+            color: Palette::default().text,
         }
     }
 
@@ -180,6 +320,47 @@ impl Sprite {
                     self.pos.x, self.pos.y, rect.w, rect.h, thickness, self.color,
                 );
             }
+            Drawable::NineSlice { nine_slice, rect } => {
+                let size = Vec2::new(rect.w, rect.h) * self.scale;
+                self.draw_nine_slice(nine_slice, size);
+            }
+        }
+    }
+
+    fn draw_nine_slice(&self, nine_slice: NineSlice, size: Vec2) {
+        let NineSlice {
+            texture,
+            margin,
+            corner,
+        } = nine_slice;
+        let corner = corner.min(size.x / 2.0).min(size.y / 2.0);
+        let (tex_w, tex_h) = (texture.width(), texture.height());
+        // Edges of the slices in the texture and on the screen.
+        let src_x = [0.0, margin, tex_w - margin, tex_w];
+        let src_y = [0.0, margin, tex_h - margin, tex_h];
+        let dst_x = [0.0, corner, size.x - corner, size.x];
+        let dst_y = [0.0, corner, size.y - corner, size.y];
+        for i in 0..3 {
+            for j in 0..3 {
+                let source = Rect::new(
+                    src_x[i],
+                    src_y[j],
+                    src_x[i + 1] - src_x[i],
+                    src_y[j + 1] - src_y[j],
+                );
+                let dest_size = Vec2::new(dst_x[i + 1] - dst_x[i], dst_y[j + 1] - dst_y[j]);
+                draw_texture_ex(
+                    texture,
+                    self.pos.x + dst_x[i],
+                    self.pos.y + dst_y[j],
+                    self.color,
+                    DrawTextureParams {
+                        dest_size: Some(dest_size),
+                        source: Some(source),
+                        ..Default::default()
+                    },
+                );
+            }
         }
     }
 
@@ -208,8 +389,45 @@ impl Sprite {
     }
 }
 
+/// Limits the drawing to the rect given in the UI coordinates
+/// and the previously pushed ones until the matching [pop_clip] call.
+fn push_clip(rect: Rect) {
+    CLIPS.with(|clips| {
+        let mut clips = clips.borrow_mut();
+        let rect = match clips.last() {
+            Some(last) => last.intersect(rect).unwrap_or_default(),
+            None => rect,
+        };
+        clips.push(rect);
+        set_clip(Some(rect));
+    });
+}
+
+fn pop_clip() {
+    CLIPS.with(|clips| {
+        let mut clips = clips.borrow_mut();
+        clips.pop();
+        set_clip(clips.last().copied());
+    });
+}
+
+/// `None` removes the limit.
+fn set_clip(rect: Option<Rect>) {
+    let clip = rect.map(|r| {
+        // The UI camera maps `-1..1` vertically to the whole screen.
+        let k = window::screen_height() / 2.0;
+        let aspect_ratio = window::screen_width() / window::screen_height();
+        let x = (r.x + aspect_ratio) * k;
+        let y = (r.y + 1.0) * k;
+        (x as i32, y as i32, (r.w * k) as i32, (r.h * k) as i32)
+    });
+    // The clip rect is a part of the draw calls' state, so it's safe
+    // to change it in the middle of a frame.
+    unsafe { get_internal_gl() }.quad_gl.scissor(clip);
+}
+
 fn make_bg(rect: Rect) -> Sprite {
-    make_rect(rect, SPRITE_COLOR_BG)
+    make_rect(rect, Palette::default().bg)
 }
 
 fn make_rect(rect: Rect, color: Color) -> Sprite {
@@ -217,6 +435,18 @@ fn make_rect(rect: Rect, color: Color) -> Sprite {
     Sprite::new(mesh, rect.h).color(color)
 }
 
+fn make_nine_slice(nine_slice: NineSlice, rect: Rect) -> Sprite {
+    Sprite::new(Drawable::NineSlice { nine_slice, rect }, rect.h).color(WHITE)
+}
+
+/// The theme's background for panels and labels.
+fn make_panel_bg(theme: &Theme, rect: Rect) -> Sprite {
+    match theme.panel {
+        Some(nine_slice) => make_nine_slice(nine_slice, rect),
+        None => make_rect(rect, theme.colors.bg),
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum VAnchor {
     Top,
@@ -231,6 +461,35 @@ pub enum HAnchor {
     Right,
 }
 
+impl VAnchor {
+    /// Shift of an item inside a slot that is `free` units higher.
+    fn offset(self, free: f32) -> f32 {
+        match self {
+            VAnchor::Top => 0.0,
+            VAnchor::Middle => free / 2.0,
+            VAnchor::Bottom => free,
+        }
+    }
+}
+
+impl HAnchor {
+    /// Shift of an item inside a slot that is `free` units wider.
+    fn offset(self, free: f32) -> f32 {
+        match self {
+            HAnchor::Left => 0.0,
+            HAnchor::Middle => free / 2.0,
+            HAnchor::Right => free,
+        }
+    }
+}
+
+/// A pressed key with the state of the modifiers.
+#[derive(Clone, Copy, Debug)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub shift: bool,
+}
+
 // TODO: Use some kind of slots? There's no point in having two panes in the same corner.
 #[derive(Clone, Copy, Debug)]
 pub struct Anchor(pub HAnchor, pub VAnchor);
@@ -244,8 +503,51 @@ pub enum StretchStatus {
 
 pub trait Widget: Debug {
     fn draw(&self);
+
+    /// Draws the floating parts on top of all the widgets.
+    fn draw_overlay(&self) {}
     fn click(&self, _: Vec2) {}
     fn move_mouse(&mut self, _: Vec2) {}
+
+    /// The mouse button is pressed, called right before `click`.
+    fn press(&mut self, _: Vec2) {}
+
+    /// The mouse button is released.
+    fn release(&mut self, _: Vec2) {}
+
+    /// The mouse wheel is turned by `delta` notches, positive values scroll up.
+    fn scroll(&mut self, _: Vec2, _delta: f32) {}
+
+    /// Number of the focusable widgets inside, including this one.
+    fn focusables(&self) -> usize {
+        0
+    }
+
+    /// Focuses the n-th focusable widget in the layouts' order, `None` removes the focus.
+    fn set_focus(&mut self, _index: Option<usize>) {}
+
+    fn focused_rect(&self) -> Option<Rect> {
+        None
+    }
+
+    /// Index of the widget under the point that takes the focus when clicked.
+    fn focusable_at(&self, _: Vec2) -> Option<usize> {
+        None
+    }
+
+    /// Returns `true` if the key was handled by a focused widget.
+    fn press_key(&mut self, _: KeyPress) -> bool {
+        false
+    }
+
+    /// Returns `true` if the character was typed into a focused widget.
+    fn type_char(&mut self, _: char) -> bool {
+        false
+    }
+
+    /// Recolors the widget and its children.
+    fn set_theme(&mut self, _: &Theme) {}
+
     fn rect(&self) -> Rect;
     fn set_pos(&mut self, pos: Vec2);
 
@@ -263,6 +565,15 @@ pub trait Widget: Debug {
         let w = self.rect().w;
         self.stretch(w)
     }
+
+    /// The widget's size has changed since the last layout,
+    /// so the parents have to be laid out again.
+    fn is_invalid(&self) -> bool {
+        false
+    }
+
+    /// Lays out the invalid children again, called only on the invalid widgets.
+    fn relayout(&mut self) {}
 }
 
 fn stretch_checks(widget: &impl Widget, width: f32) -> Option<StretchStatus> {
@@ -277,6 +588,113 @@ fn stretch_checks(widget: &impl Widget, width: f32) -> Option<StretchStatus> {
 
 pub type RcWidget = Rc<RefCell<dyn Widget>>;
 
+/// A shared widget can be a layout's child and still be modified from the outside,
+/// like a label with the text that changes.
+impl<W: Widget + ?Sized> Widget for Rc<RefCell<W>> {
+    fn draw(&self) {
+        self.borrow().draw();
+    }
+
+    fn draw_overlay(&self) {
+        self.borrow().draw_overlay();
+    }
+
+    fn click(&self, pos: Vec2) {
+        self.borrow().click(pos);
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        self.borrow_mut().move_mouse(pos);
+    }
+
+    fn press(&mut self, pos: Vec2) {
+        self.borrow_mut().press(pos);
+    }
+
+    fn release(&mut self, pos: Vec2) {
+        self.borrow_mut().release(pos);
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) {
+        self.borrow_mut().scroll(pos, delta);
+    }
+
+    fn focusables(&self) -> usize {
+        self.borrow().focusables()
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.borrow_mut().set_focus(index);
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        self.borrow().focused_rect()
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        self.borrow().focusable_at(pos)
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        self.borrow_mut().press_key(key)
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        self.borrow_mut().type_char(c)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.borrow_mut().set_theme(theme);
+    }
+
+    fn rect(&self) -> Rect {
+        self.borrow().rect()
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.borrow_mut().set_pos(pos);
+    }
+
+    fn can_stretch(&self) -> bool {
+        self.borrow().can_stretch()
+    }
+
+    fn stretch(&mut self, width: f32) -> StretchStatus {
+        self.borrow_mut().stretch(width)
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.borrow().is_invalid()
+    }
+
+    fn relayout(&mut self) {
+        self.borrow_mut().relayout();
+    }
+}
+
+/// Index of the focusable under the point counting through all the widgets.
+fn focusable_at<W: std::ops::Deref<Target = dyn Widget>>(
+    widgets: impl Iterator<Item = W>,
+    pos: Vec2,
+) -> Option<usize> {
+    let mut offset = 0;
+    for widget in widgets {
+        if let Some(index) = widget.focusable_at(pos) {
+            return Some(offset + index);
+        }
+        offset += widget.focusables();
+    }
+    None
+}
+
+/// Passes the focus to the widget that has the n-th focusable inside
+/// and returns the index left for the next widgets.
+fn pass_focus(widget: &mut dyn Widget, index: Option<usize>) -> Option<usize> {
+    let count = widget.focusables();
+    widget.set_focus(index.filter(|&i| i < count));
+    index.and_then(|i| i.checked_sub(count))
+}
+
 #[derive(Debug)]
 pub struct AnchoredWidget {
     widget: RcWidget,
@@ -289,6 +707,11 @@ pub struct Gui<Message: Clone> {
     anchored_widgets: Vec<AnchoredWidget>,
     receiver: Receiver<Message>,
     sender: Sender<Message>,
+    focus: Option<usize>,
+    theme: Theme,
+
+    /// Sent when the Escape key isn't handled by the focused widget.
+    cancel_message: Option<Message>,
 }
 
 impl<Message: Clone> Gui<Message> {
@@ -301,7 +724,37 @@ impl<Message: Clone> Gui<Message> {
             receiver,
             sender,
             aspect_ratio,
+            focus: None,
+            theme: Theme::default(),
+            cancel_message: None,
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.set_theme(theme);
+        self
+    }
+
+    /// Applies the theme to all the widgets, including the ones added later.
+    pub fn set_theme(&mut self, theme: Theme) {
+        for AnchoredWidget { widget, .. } in &self.anchored_widgets {
+            widget.borrow_mut().set_theme(&theme);
         }
+        self.theme = theme;
+        self.resize(self.aspect_ratio);
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn with_cancel_message(mut self, message: Message) -> Self {
+        self.set_cancel_message(message);
+        self
+    }
+
+    pub fn set_cancel_message(&mut self, message: Message) {
+        self.cancel_message = Some(message);
     }
 
     /// Returns a clone of sender
@@ -311,20 +764,24 @@ impl<Message: Clone> Gui<Message> {
 
     pub fn add(&mut self, widget: &RcWidget, anchor: Anchor) {
         let widget = widget.clone();
+        widget.borrow_mut().set_theme(&self.theme);
         let anchored_widget = AnchoredWidget { widget, anchor };
         self.anchored_widgets.push(anchored_widget);
         self.resize(self.aspect_ratio);
+        self.set_focus(self.focus);
     }
 
-    pub fn remove(&mut self, widget: &RcWidget) {
-        let anchored_widgets = &mut self.anchored_widgets;
-        let len_before = anchored_widgets.len();
-        anchored_widgets.retain(|w| !Rc::ptr_eq(&w.widget, widget));
-        let len_after = anchored_widgets.len();
-        info!("len_before={}, len_after={}", len_before, len_after);
-        if len_after != len_before - 1 {
-            panic!("Can't remove the widget");
-        }
+    /// Fails if the widget wasn't added to the gui.
+    pub fn remove(&mut self, widget: &RcWidget) -> Result {
+        let index = self
+            .anchored_widgets
+            .iter()
+            .position(|w| Rc::ptr_eq(&w.widget, widget))
+            .ok_or(Error::NoSuchWidget)?;
+        self.anchored_widgets.remove(index);
+        info!("Gui::remove: {} widgets left", self.anchored_widgets.len());
+        self.set_focus(self.focus);
+        Ok(())
     }
 
     pub fn draw(&self) {
@@ -333,15 +790,111 @@ impl<Message: Clone> Gui<Message> {
         for AnchoredWidget { widget, .. } in &self.anchored_widgets {
             widget.borrow().draw();
         }
+        for AnchoredWidget { widget, .. } in &self.anchored_widgets {
+            widget.borrow().draw_overlay();
+        }
     }
 
+    /// Messages sent outside of the `click` and `press_key` calls,
+    /// like the ones from the dragged sliders.
+    pub fn messages(&self) -> impl Iterator<Item = Message> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Also moves the focus to the clicked widget or removes it.
     pub fn click(&mut self, pos: Vec2) -> Option<Message> {
         for AnchoredWidget { widget, .. } in &self.anchored_widgets {
-            widget.borrow_mut().click(pos);
+            let mut widget = widget.borrow_mut();
+            widget.press(pos);
+            widget.click(pos);
+        }
+        self.set_focus(self.focusable_at(pos));
+        self.receiver.try_recv().ok()
+    }
+
+    pub fn release(&mut self, pos: Vec2) {
+        for AnchoredWidget { widget, .. } in &self.anchored_widgets {
+            widget.borrow_mut().release(pos);
+        }
+    }
+
+    pub fn scroll(&mut self, pos: Vec2, delta: f32) {
+        for AnchoredWidget { widget, .. } in &self.anchored_widgets {
+            widget.borrow_mut().scroll(pos, delta);
+        }
+    }
+
+    /// Gives the key to the focused widget first, then moves
+    /// the focus with Tab and the arrows or sends the cancel message.
+    pub fn press_key(&mut self, key: KeyPress) -> Option<Message> {
+        let is_handled = self
+            .anchored_widgets
+            .iter()
+            .any(|AnchoredWidget { widget, .. }| widget.borrow_mut().press_key(key));
+        if !is_handled {
+            match key.code {
+                KeyCode::Tab if key.shift => self.focus_prev(),
+                KeyCode::Tab | KeyCode::Down | KeyCode::Right => self.focus_next(),
+                KeyCode::Up | KeyCode::Left => self.focus_prev(),
+                KeyCode::Escape => {
+                    if let Some(message) = self.cancel_message.clone() {
+                        self.sender.send(message).unwrap();
+                    }
+                }
+                _ => {}
+            }
         }
         self.receiver.try_recv().ok()
     }
 
+    pub fn type_char(&mut self, c: char) {
+        for AnchoredWidget { widget, .. } in &self.anchored_widgets {
+            if widget.borrow_mut().type_char(c) {
+                break;
+            }
+        }
+    }
+
+    pub fn focus_next(&mut self) {
+        let count = self.focusables();
+        let index = match self.focus {
+            _ if count == 0 => None,
+            Some(i) => Some((i + 1) % count),
+            None => Some(0),
+        };
+        self.set_focus(index);
+    }
+
+    pub fn focus_prev(&mut self) {
+        let count = self.focusables();
+        let index = match self.focus {
+            _ if count == 0 => None,
+            Some(i) => Some((i + count - 1) % count),
+            None => Some(count - 1),
+        };
+        self.set_focus(index);
+    }
+
+    pub fn set_focus(&mut self, index: Option<usize>) {
+        self.focus = index.filter(|&i| i < self.focusables());
+        let mut index = self.focus;
+        for AnchoredWidget { widget, .. } in &self.anchored_widgets {
+            index = pass_focus(&mut *widget.borrow_mut(), index);
+        }
+    }
+
+    fn focusables(&self) -> usize {
+        self.anchored_widgets
+            .iter()
+            .map(|AnchoredWidget { widget, .. }| widget.borrow().focusables())
+            .sum()
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        let widgets = self.anchored_widgets.iter().map(|w| w.widget.borrow());
+        focusable_at(widgets, pos)
+    }
+
     pub fn move_mouse(&mut self, pos: Vec2) {
         for AnchoredWidget { widget, .. } in &self.anchored_widgets {
             widget.borrow_mut().move_mouse(pos);
@@ -351,7 +904,7 @@ impl<Message: Clone> Gui<Message> {
     pub fn resize(&mut self, ratio: f32) {
         self.aspect_ratio = ratio;
         trace!("Gui::resize: {}", ratio);
-        let offset = 0.02; // TODO: make configurable
+        let offset = self.theme.padding;
         for AnchoredWidget { widget, anchor } in &mut self.anchored_widgets {
             let mut widget = widget.borrow_mut();
             let rect = widget.rect();
@@ -370,10 +923,32 @@ impl<Message: Clone> Gui<Message> {
         }
     }
 
+    /// Also lays out the widgets that have changed their sizes.
     pub fn resize_if_needed(&mut self, aspect_ratio: f32) {
-        if (self.aspect_ratio - aspect_ratio).abs() > f32::EPSILON {
-            self.resize(aspect_ratio);
+        let is_resized = (self.aspect_ratio - aspect_ratio).abs() > f32::EPSILON;
+        if is_resized || self.is_invalid() {
+            self.aspect_ratio = aspect_ratio;
+            self.relayout();
+        }
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.anchored_widgets
+            .iter()
+            .any(|AnchoredWidget { widget, .. }| widget.borrow().is_invalid())
+    }
+
+    /// Lays out the invalid widgets again and re-anchors all of them.
+    pub fn relayout(&mut self) {
+        for AnchoredWidget { widget, .. } in &self.anchored_widgets {
+            let mut widget = widget.borrow_mut();
+            if widget.is_invalid() {
+                widget.relayout();
+            }
         }
+        self.resize(self.aspect_ratio);
+        // The focused widget may have been removed from its layout.
+        self.set_focus(self.focus);
     }
 }
 
@@ -419,6 +994,13 @@ pub struct Label {
     param: LabelParam,
     rect: Rect,
     height: f32,
+    theme: Theme,
+
+    /// Overrides the theme's text color.
+    color: Option<Color>,
+
+    /// The drawable has been replaced since the last layout.
+    is_invalid: bool,
 }
 
 impl Label {
@@ -450,6 +1032,9 @@ impl Label {
             param,
             rect,
             height,
+            theme: Theme::default(),
+            color: None,
+            is_invalid: false,
         })
     }
 
@@ -468,8 +1053,37 @@ impl Label {
     }
 
     pub fn set_color(&mut self, color: Color) {
+        self.color = Some(color);
         self.sprite.color = color;
     }
+
+    /// Keeps the font, fails if the label isn't a text.
+    pub fn set_text(&mut self, text: impl Into<String>) -> Result {
+        let font = match self.sprite.drawable {
+            Drawable::Text { font, .. } => font,
+            _ => return Err(Error::NotText),
+        };
+        self.set_drawable(Drawable::text(text, font));
+        Ok(())
+    }
+
+    /// The label gets its natural width back, the parents
+    /// are laid out again by [Gui::relayout].
+    pub fn set_drawable(&mut self, drawable: Drawable) {
+        let pos = self.rect.point();
+        let color = self.sprite.color;
+        self.sprite = Sprite::new(drawable, self.height * self.param.drawable_k).color(color);
+        self.rect = Rect {
+            w: self.sprite.rect().w,
+            h: self.sprite.rect().bottom() / self.param.drawable_k,
+            ..Default::default()
+        };
+        if self.param.bg {
+            self.bg = Some(make_panel_bg(&self.theme, self.rect));
+        }
+        self.set_pos(pos);
+        self.is_invalid = true;
+    }
 }
 
 impl Widget for Label {
@@ -512,11 +1126,29 @@ impl Widget for Label {
         };
         self.rect = rect;
         if self.param.bg {
-            self.bg = Some(make_bg(rect));
+            self.bg = Some(make_panel_bg(&self.theme, rect));
         }
         self.set_pos(pos);
         StretchStatus::Stretched
     }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.sprite.color = self.color.unwrap_or(theme.colors.text);
+        if self.param.bg {
+            let mut bg = make_panel_bg(theme, Rect::new(0.0, 0.0, self.rect.w, self.rect.h));
+            bg.set_pos(self.rect.point());
+            self.bg = Some(bg);
+        }
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.is_invalid
+    }
+
+    fn relayout(&mut self) {
+        self.is_invalid = false;
+    }
 }
 
 #[derive(Debug)]
@@ -578,25 +1210,92 @@ impl Widget for ColoredRect {
     }
 }
 
+/// The theme's background.
 #[derive(Debug)]
-pub struct Spacer {
-    rect: Rect,
+pub struct Panel {
+    sprite: Sprite,
+    theme: Theme,
     is_stretchable: bool,
 }
 
-impl Spacer {
+impl Panel {
     pub fn new(rect: Rect) -> Self {
         Self {
-            rect,
+            sprite: make_bg(rect),
+            theme: Theme::default(),
             is_stretchable: false,
         }
     }
 
-    pub fn new_vertical(h: f32) -> Self {
-        let rect = Rect {
-            h,
-            ..Default::default()
-        };
+    pub fn stretchable(mut self, value: bool) -> Self {
+        self.set_stretchable(value);
+        self
+    }
+
+    pub fn set_stretchable(&mut self, value: bool) {
+        self.is_stretchable = value;
+    }
+}
+
+impl Widget for Panel {
+    fn draw(&self) {
+        self.sprite.draw()
+    }
+
+    fn rect(&self) -> Rect {
+        self.sprite.rect()
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.sprite.set_pos(pos);
+    }
+
+    fn can_stretch(&self) -> bool {
+        self.is_stretchable
+    }
+
+    fn stretch(&mut self, width: f32) -> StretchStatus {
+        if let Some(status) = stretch_checks(self, width) {
+            return status;
+        }
+        let pos = self.rect().point();
+        let rect = Rect {
+            w: width,
+            h: self.rect().h,
+            ..Default::default()
+        };
+        self.sprite = make_panel_bg(&self.theme, rect);
+        self.set_pos(pos);
+        StretchStatus::Stretched
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        let rect = self.rect();
+        self.sprite = make_panel_bg(theme, Rect::new(0.0, 0.0, rect.w, rect.h));
+        self.set_pos(rect.point());
+    }
+}
+
+#[derive(Debug)]
+pub struct Spacer {
+    rect: Rect,
+    is_stretchable: bool,
+}
+
+impl Spacer {
+    pub fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            is_stretchable: false,
+        }
+    }
+
+    pub fn new_vertical(h: f32) -> Self {
+        let rect = Rect {
+            h,
+            ..Default::default()
+        };
         Self {
             rect,
             is_stretchable: false,
@@ -675,23 +1374,46 @@ impl ButtonParam {
         if self.drawable_k < 0.0 || self.drawable_k > 1.0 {
             return Err(Error::BadContentCoefficient);
         }
-        if self.border_k * 2.0 > 1.0 - self.drawable_k {
+        if self.border_k < 0.0 || self.border_k * 2.0 > 1.0 - self.drawable_k {
             return Err(Error::BadBorderCoefficient);
         }
         Ok(())
     }
+
+    /// The theme's override if it's valid for these params, the own value otherwise.
+    fn border_k(&self, theme: &Theme) -> f32 {
+        match theme.border_k {
+            Some(border_k)
+                if Self {
+                    border_k,
+                    ..self.clone()
+                }
+                .check()
+                .is_ok() =>
+            {
+                border_k
+            }
+            _ => self.border_k,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Button<Message: Clone> {
     is_active: bool,
+    is_hovered: bool,
+    is_focused: bool,
+    is_pressed: bool,
     sprite: Sprite,
     bg: Sprite,
     border: Sprite,
     param: ButtonParam,
     sender: Sender<Message>,
     message: Message,
-    color: Color,
+    theme: Theme,
+
+    /// Overrides the theme's text color.
+    color: Option<Color>,
 }
 
 impl<Message: Clone + Debug> Button<Message> {
@@ -719,29 +1441,29 @@ impl<Message: Clone + Debug> Button<Message> {
         let bg = Self::make_bg_mesh(height, outer);
         Ok(Self {
             is_active: true,
+            is_hovered: false,
+            is_focused: false,
+            is_pressed: false,
             sprite,
             bg,
             border,
             param,
             sender,
             message,
-            color: SPRITE_COLOR,
+            theme: Theme::default(),
+            color: None,
         })
     }
 
+    /// Used while the button is active.
     pub fn set_color(&mut self, color: Color) {
-        self.color = color;
-        self.sprite.color = self.color;
+        self.color = Some(color);
+        self.update_highlight();
     }
 
     pub fn set_active(&mut self, value: bool) {
         self.is_active = value;
-        let color = if value {
-            SPRITE_COLOR
-        } else {
-            SPRITE_COLOR_INACTIVE
-        };
-        self.set_color(color);
+        self.update_highlight();
     }
 
     pub fn is_active(&self) -> bool {
@@ -771,20 +1493,52 @@ impl<Message: Clone + Debug> Button<Message> {
     fn make_border(height: f32, rect: Rect, thickness: f32) -> Sprite {
         let thickness = height * thickness * 2.0; // TODO: why do we need this 2.0 here?
         let bg_mesh = Drawable::LinesRect { rect, thickness };
-        Sprite::new(bg_mesh, height).color(SPRITE_COLOR_BUTTON_BORDER)
+        Sprite::new(bg_mesh, height).color(Palette::default().border)
     }
 
     fn make_bg_mesh(height: f32, outer: Rect) -> Sprite {
         let bg_mesh = Drawable::SolidRect { rect: outer };
-        Sprite::new(bg_mesh, height).color(SPRITE_COLOR_BG)
+        Sprite::new(bg_mesh, height).color(Palette::default().bg)
+    }
+
+    fn update_highlight(&mut self) {
+        let colors = &self.theme.colors;
+        self.sprite.color = if self.is_active {
+            self.color.unwrap_or(colors.text)
+        } else {
+            colors.text_inactive
+        };
+        let (bg, border) = frame_colors(colors, self.is_hovered, self.is_focused);
+        self.border.color = border;
+        match self.theme.button {
+            Some(textures) => {
+                let rect = self.rect();
+                let state = if !self.is_active {
+                    textures.disabled
+                } else if self.is_pressed {
+                    textures.pressed
+                } else if self.is_hovered || self.is_focused {
+                    textures.hovered
+                } else {
+                    None
+                };
+                let nine_slice = state.unwrap_or(textures.normal);
+                self.bg = make_nine_slice(nine_slice, Rect::new(0.0, 0.0, rect.w, rect.h));
+                self.bg.set_pos(rect.point());
+            }
+            None => self.bg.color = bg,
+        }
     }
 }
 
 impl<Message: Clone + Debug> Widget for Button<Message> {
+    /// The textured buttons draw the border only when focused.
     fn draw(&self) {
         self.bg.draw();
         self.sprite.draw();
-        self.border.draw();
+        if self.theme.button.is_none() || self.is_focused {
+            self.border.draw();
+        }
     }
 
     fn click(&self, pos: Vec2) {
@@ -796,13 +1550,52 @@ impl<Message: Clone + Debug> Widget for Button<Message> {
     }
 
     fn move_mouse(&mut self, pos: Vec2) {
-        let highlighted = self.border.rect().contains(pos);
-        if highlighted {
-            self.bg.color = SPRITE_COLOR_BG_HIGHLIGHTED;
+        self.is_hovered = self.border.rect().contains(pos);
+        self.update_highlight();
+    }
+
+    fn press(&mut self, pos: Vec2) {
+        if self.border.rect().contains(pos) {
+            self.is_pressed = true;
+            self.update_highlight();
+        }
+    }
+
+    fn release(&mut self, _: Vec2) {
+        if self.is_pressed {
+            self.is_pressed = false;
+            self.update_highlight();
+        }
+    }
+
+    fn focusables(&self) -> usize {
+        if self.is_active {
+            1
         } else {
-            self.sprite.color = self.color;
-            self.bg.color = SPRITE_COLOR_BG;
-        };
+            0
+        }
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.is_focused = index.is_some();
+        self.update_highlight();
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        if self.is_focused {
+            Some(self.rect())
+        } else {
+            None
+        }
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        if !self.is_focused || !is_activation_key(key) {
+            return false;
+        }
+        let message = self.message.clone();
+        self.sender.send(message).unwrap();
+        true
     }
 
     fn rect(&self) -> Rect {
@@ -832,32 +1625,160 @@ impl<Message: Clone + Debug> Widget for Button<Message> {
             h: self.rect().h,
             ..Default::default()
         };
-        self.border = Self::make_border(height, outer, self.param.border_k);
+        self.border = Self::make_border(height, outer, self.param.border_k(&self.theme));
         self.bg = Self::make_bg_mesh(height, outer);
+        self.update_highlight();
         self.set_pos(pos);
         StretchStatus::Stretched
     }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        let pos = self.rect().point();
+        let height = self.bg.dimensions.h;
+        let outer = Rect {
+            x: 0.0,
+            y: 0.0,
+            ..self.rect()
+        };
+        self.border = Self::make_border(height, outer, self.param.border_k(theme));
+        self.bg = Self::make_bg_mesh(height, outer);
+        self.set_pos(pos);
+        self.update_highlight();
+    }
 }
 
-#[derive(Debug, Default)]
+/// Identifies a child of a [VLayout] or an [HLayout].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle(usize);
+
+#[derive(Debug)]
 struct Layout {
     widgets: Vec<Box<dyn Widget>>,
+
+    /// Parallel to `widgets`.
+    handles: Vec<Handle>,
+
+    next_handle: usize,
+
+    /// The last applied theme, it's given to the children added later.
+    theme: Option<Theme>,
+
+    /// The children have been added or removed since the last layout.
+    is_invalid: bool,
+
     rect: Rect,
     is_stretchable: bool,
+
+    /// Distance between the neighbor children.
+    spacing: f32,
+
+    /// Distance between the children and the layout's edges.
+    padding: f32,
+
+    /// Position of the children inside of their bigger slots.
+    align: Anchor,
+
+    /// The layout is kept at least this wide after being stretched.
+    stretched_w: f32,
 }
 
 impl Layout {
     fn new() -> Self {
         Self {
             widgets: Vec::new(),
+            handles: Vec::new(),
+            next_handle: 0,
+            theme: None,
+            is_invalid: false,
             rect: Rect::default(),
             is_stretchable: false,
+            spacing: 0.0,
+            padding: 0.0,
+            align: Anchor(HAnchor::Left, VAnchor::Top),
+            stretched_w: 0.0,
         }
     }
 
     pub fn set_stretchable(&mut self, value: bool) {
         self.is_stretchable = value;
     }
+
+    fn max_child_size(&self) -> Vec2 {
+        self.widgets
+            .iter()
+            .fold(Vec2::new(0.0, 0.0), |size, widget| {
+                let rect = widget.rect();
+                Vec2::new(size.x.max(rect.w), size.y.max(rect.h))
+            })
+    }
+
+    /// Total spacing between the children.
+    fn gaps(&self) -> f32 {
+        self.spacing * self.widgets.len().saturating_sub(1) as f32
+    }
+
+    fn new_handle(&mut self, widget: &mut dyn Widget) -> Handle {
+        if let Some(ref theme) = self.theme {
+            widget.set_theme(theme);
+        }
+        let handle = Handle(self.next_handle);
+        self.next_handle += 1;
+        handle
+    }
+
+    fn push(&mut self, mut widget: Box<dyn Widget>) -> Handle {
+        let handle = self.new_handle(widget.as_mut());
+        self.widgets.push(widget);
+        self.handles.push(handle);
+        handle
+    }
+
+    fn index(&self, handle: Handle) -> Result<usize> {
+        self.handles
+            .iter()
+            .position(|&h| h == handle)
+            .ok_or(Error::NoSuchWidget)
+    }
+
+    fn insert_before(&mut self, handle: Handle, mut widget: Box<dyn Widget>) -> Result<Handle> {
+        let index = self.index(handle)?;
+        let new_handle = self.new_handle(widget.as_mut());
+        self.widgets.insert(index, widget);
+        self.handles.insert(index, new_handle);
+        self.is_invalid = true;
+        Ok(new_handle)
+    }
+
+    fn remove(&mut self, handle: Handle) -> Result<Box<dyn Widget>> {
+        let index = self.index(handle)?;
+        self.handles.remove(index);
+        self.is_invalid = true;
+        Ok(self.widgets.remove(index))
+    }
+
+    fn replace(&mut self, handle: Handle, mut widget: Box<dyn Widget>) -> Result<Box<dyn Widget>> {
+        let index = self.index(handle)?;
+        if let Some(ref theme) = self.theme {
+            widget.set_theme(theme);
+        }
+        self.is_invalid = true;
+        Ok(std::mem::replace(&mut self.widgets[index], widget))
+    }
+
+    /// Stretches the children to the layout's stretched width.
+    fn stretch_children(&mut self) {
+        let inner_w = self.stretched_w - self.padding * 2.0;
+        for widget in &mut self.widgets {
+            widget.stretch(inner_w);
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Widget for Layout {
@@ -867,6 +1788,12 @@ impl Widget for Layout {
         }
     }
 
+    fn draw_overlay(&self) {
+        for widget in &self.widgets {
+            widget.draw_overlay();
+        }
+    }
+
     fn click(&self, pos: Vec2) {
         for widget in &self.widgets {
             widget.click(pos);
@@ -879,6 +1806,57 @@ impl Widget for Layout {
         }
     }
 
+    fn press(&mut self, pos: Vec2) {
+        for widget in &mut self.widgets {
+            widget.press(pos);
+        }
+    }
+
+    fn release(&mut self, pos: Vec2) {
+        for widget in &mut self.widgets {
+            widget.release(pos);
+        }
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) {
+        for widget in &mut self.widgets {
+            widget.scroll(pos, delta);
+        }
+    }
+
+    fn focusables(&self) -> usize {
+        self.widgets.iter().map(|widget| widget.focusables()).sum()
+    }
+
+    fn set_focus(&mut self, mut index: Option<usize>) {
+        for widget in &mut self.widgets {
+            index = pass_focus(widget.as_mut(), index);
+        }
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        self.widgets.iter().find_map(|widget| widget.focused_rect())
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        focusable_at(self.widgets.iter().map(|widget| widget.as_ref()), pos)
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        self.widgets.iter_mut().any(|widget| widget.press_key(key))
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        self.widgets.iter_mut().any(|widget| widget.type_char(c))
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        for widget in &mut self.widgets {
+            widget.set_theme(theme);
+        }
+        self.theme = Some(theme.clone());
+    }
+
     fn rect(&self) -> Rect {
         self.rect
     }
@@ -896,15 +1874,28 @@ impl Widget for Layout {
         self.is_stretchable
     }
 
+    /// Stretches the children, the rect is updated by the specific layouts.
     fn stretch(&mut self, width: f32) -> StretchStatus {
         if let Some(status) = stretch_checks(self, width) {
             return status;
         }
+        self.stretched_w = width;
+        self.stretch_children();
+        StretchStatus::Stretched
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.is_invalid || self.widgets.iter().any(|widget| widget.is_invalid())
+    }
+
+    /// Only the children, the rect is updated by the specific layouts.
+    fn relayout(&mut self) {
         for widget in &mut self.widgets {
-            widget.stretch(width);
-            self.rect.w = self.rect.w.max(widget.rect().w);
+            if widget.is_invalid() {
+                widget.relayout();
+            }
         }
-        StretchStatus::Stretched
+        self.is_invalid = false;
     }
 }
 
@@ -925,27 +1916,75 @@ impl VLayout {
         self
     }
 
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.internal.spacing = spacing;
+        self.layout();
+        self
+    }
+
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.internal.padding = padding;
+        self.layout();
+        self
+    }
+
+    /// Aligns the narrower children.
+    pub fn with_align(mut self, align: HAnchor) -> Self {
+        self.internal.align.0 = align;
+        self.layout();
+        self
+    }
+
     pub fn from_widget(widget: Box<dyn Widget>) -> Self {
         let mut this = Self::new();
         this.add(widget);
         this
     }
 
-    pub fn add(&mut self, mut widget: Box<dyn Widget>) {
-        let rect = widget.rect();
-        if let Some(last) = self.internal.widgets.last() {
-            let rect = last.rect();
-            let mut pos = rect.point();
-            pos.y += rect.h;
-            widget.set_pos(pos);
-        } else {
-            widget.set_pos(self.internal.rect.point());
-        }
-        self.internal.widgets.push(widget);
-        self.internal.rect.h += rect.h;
-        if self.internal.rect.w < rect.w {
-            self.internal.rect.w = rect.w;
+    pub fn add(&mut self, widget: Box<dyn Widget>) -> Handle {
+        let handle = self.internal.push(widget);
+        self.layout();
+        handle
+    }
+
+    pub fn insert_before(&mut self, handle: Handle, widget: Box<dyn Widget>) -> Result<Handle> {
+        let new_handle = self.internal.insert_before(handle, widget)?;
+        self.layout();
+        Ok(new_handle)
+    }
+
+    /// Returns the removed child.
+    pub fn remove(&mut self, handle: Handle) -> Result<Box<dyn Widget>> {
+        let widget = self.internal.remove(handle)?;
+        self.layout();
+        Ok(widget)
+    }
+
+    /// The new child keeps the handle, the old one is returned.
+    pub fn replace(&mut self, handle: Handle, widget: Box<dyn Widget>) -> Result<Box<dyn Widget>> {
+        let old_widget = self.internal.replace(handle, widget)?;
+        self.layout();
+        Ok(old_widget)
+    }
+
+    fn layout(&mut self) {
+        let layout = &mut self.internal;
+        let padding = layout.padding;
+        let w = (layout.max_child_size().x + padding * 2.0).max(layout.stretched_w);
+        let inner_w = w - padding * 2.0;
+        let origin = layout.rect.point();
+        let mut y = origin.y + padding;
+        for (i, widget) in layout.widgets.iter_mut().enumerate() {
+            if i != 0 {
+                y += layout.spacing;
+            }
+            let rect = widget.rect();
+            let x = origin.x + padding + layout.align.0.offset(inner_w - rect.w);
+            widget.set_pos(Vec2::new(x, y));
+            y += rect.h;
         }
+        layout.rect.w = w;
+        layout.rect.h = y + padding - origin.y;
     }
 }
 
@@ -954,6 +1993,10 @@ impl Widget for VLayout {
         self.internal.draw()
     }
 
+    fn draw_overlay(&self) {
+        self.internal.draw_overlay()
+    }
+
     fn click(&self, pos: Vec2) {
         self.internal.click(pos);
     }
@@ -962,6 +2005,46 @@ impl Widget for VLayout {
         self.internal.move_mouse(pos);
     }
 
+    fn press(&mut self, pos: Vec2) {
+        self.internal.press(pos);
+    }
+
+    fn release(&mut self, pos: Vec2) {
+        self.internal.release(pos);
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) {
+        self.internal.scroll(pos, delta);
+    }
+
+    fn focusables(&self) -> usize {
+        self.internal.focusables()
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.internal.set_focus(index);
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        self.internal.focused_rect()
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        self.internal.focusable_at(pos)
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        self.internal.press_key(key)
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        self.internal.type_char(c)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.internal.set_theme(theme);
+    }
+
     fn rect(&self) -> Rect {
         self.internal.rect()
     }
@@ -975,7 +2058,21 @@ impl Widget for VLayout {
     }
 
     fn stretch(&mut self, width: f32) -> StretchStatus {
-        self.internal.stretch(width)
+        let status = self.internal.stretch(width);
+        self.layout();
+        status
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.internal.is_invalid()
+    }
+
+    fn relayout(&mut self) {
+        self.internal.relayout();
+        if self.internal.stretched_w > 0.0 {
+            self.internal.stretch_children();
+        }
+        self.layout();
     }
 }
 
@@ -996,37 +2093,153 @@ impl HLayout {
         self
     }
 
-    pub fn add(&mut self, mut widget: Box<dyn Widget>) {
-        let rect = widget.rect();
-        if let Some(last) = self.internal.widgets.last() {
-            let rect = last.rect();
-            widget.set_pos(rect.point() + Vec2::new(rect.w, 0.0));
-        } else {
-            widget.set_pos(self.internal.rect.point());
-        }
-        self.internal.rect.w += rect.w;
-        if self.internal.rect.h < rect.h {
-            self.internal.rect.h = rect.h;
-        }
-        self.internal.widgets.push(widget);
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.internal.spacing = spacing;
+        self.layout();
+        self
     }
-}
 
-impl Widget for HLayout {
-    fn draw(&self) {
-        self.internal.draw()
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.internal.padding = padding;
+        self.layout();
+        self
     }
 
-    fn click(&self, pos: Vec2) {
-        self.internal.click(pos);
+    /// Aligns the lower children.
+    pub fn with_align(mut self, align: VAnchor) -> Self {
+        self.internal.align.1 = align;
+        self.layout();
+        self
     }
 
-    fn move_mouse(&mut self, pos: Vec2) {
-        self.internal.move_mouse(pos);
+    pub fn add(&mut self, widget: Box<dyn Widget>) -> Handle {
+        let handle = self.internal.push(widget);
+        self.layout();
+        handle
     }
 
-    fn rect(&self) -> Rect {
-        self.internal.rect()
+    pub fn insert_before(&mut self, handle: Handle, widget: Box<dyn Widget>) -> Result<Handle> {
+        let new_handle = self.internal.insert_before(handle, widget)?;
+        self.layout();
+        Ok(new_handle)
+    }
+
+    /// Returns the removed child.
+    pub fn remove(&mut self, handle: Handle) -> Result<Box<dyn Widget>> {
+        let widget = self.internal.remove(handle)?;
+        self.layout();
+        Ok(widget)
+    }
+
+    /// The new child keeps the handle, the old one is returned.
+    pub fn replace(&mut self, handle: Handle, widget: Box<dyn Widget>) -> Result<Box<dyn Widget>> {
+        let old_widget = self.internal.replace(handle, widget)?;
+        self.layout();
+        Ok(old_widget)
+    }
+
+    /// Divides the extra width between the stretchable children.
+    fn stretch_children(&mut self) {
+        let taken_w = self.content_w();
+        let width = self.internal.stretched_w;
+        let widgets = &mut self.internal.widgets;
+        let stretchable_count = widgets.iter().filter(|w| w.can_stretch()).count();
+        if stretchable_count > 0 {
+            let additional_w_per_stretchable = (width - taken_w) / stretchable_count as f32;
+            for widget in widgets.iter_mut().filter(|w| w.can_stretch()) {
+                let new_w = widget.rect().w + additional_w_per_stretchable;
+                widget.stretch(new_w);
+            }
+        }
+    }
+
+    fn content_w(&self) -> f32 {
+        let children_w: f32 = self.internal.widgets.iter().map(|w| w.rect().w).sum();
+        children_w + self.internal.gaps() + self.internal.padding * 2.0
+    }
+
+    /// The row of children is aligned horizontally when there's nothing to stretch.
+    fn layout(&mut self) {
+        let content_w = self.content_w();
+        let layout = &mut self.internal;
+        let padding = layout.padding;
+        let w = content_w.max(layout.stretched_w);
+        let inner_h = layout.max_child_size().y;
+        let origin = layout.rect.point();
+        let mut x = origin.x + padding + layout.align.0.offset(w - content_w);
+        for (i, widget) in layout.widgets.iter_mut().enumerate() {
+            if i != 0 {
+                x += layout.spacing;
+            }
+            let rect = widget.rect();
+            let y = origin.y + padding + layout.align.1.offset(inner_h - rect.h);
+            widget.set_pos(Vec2::new(x, y));
+            x += rect.w;
+        }
+        layout.rect.w = w;
+        layout.rect.h = inner_h + padding * 2.0;
+    }
+}
+
+impl Widget for HLayout {
+    fn draw(&self) {
+        self.internal.draw()
+    }
+
+    fn draw_overlay(&self) {
+        self.internal.draw_overlay()
+    }
+
+    fn click(&self, pos: Vec2) {
+        self.internal.click(pos);
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        self.internal.move_mouse(pos);
+    }
+
+    fn press(&mut self, pos: Vec2) {
+        self.internal.press(pos);
+    }
+
+    fn release(&mut self, pos: Vec2) {
+        self.internal.release(pos);
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) {
+        self.internal.scroll(pos, delta);
+    }
+
+    fn focusables(&self) -> usize {
+        self.internal.focusables()
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.internal.set_focus(index);
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        self.internal.focused_rect()
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        self.internal.focusable_at(pos)
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        self.internal.press_key(key)
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        self.internal.type_char(c)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.internal.set_theme(theme);
+    }
+
+    fn rect(&self) -> Rect {
+        self.internal.rect()
     }
 
     fn set_pos(&mut self, pos: Vec2) {
@@ -1041,23 +2254,23 @@ impl Widget for HLayout {
         if let Some(status) = stretch_checks(self, width) {
             return status;
         }
-        let widgets = &mut self.internal.widgets;
-        let stretchable_count = widgets.iter().filter(|w| w.can_stretch()).count();
-        let taken_w: f32 = widgets.iter().fold(0.0, |acc, w| acc + w.rect().w);
-        let additional_w_per_stretchable = (width - taken_w) / stretchable_count as f32;
-        let mut diff_w = 0.0;
-        for widget in widgets {
-            let r = widget.rect();
-            widget.set_pos(r.point() + Vec2::new(diff_w, 0.0));
-            if widget.can_stretch() {
-                let new_w = r.w + additional_w_per_stretchable;
-                widget.stretch(new_w);
-                diff_w += additional_w_per_stretchable;
-            }
-        }
-        self.internal.rect.w = width;
+        self.internal.stretched_w = width;
+        self.stretch_children();
+        self.layout();
         StretchStatus::Stretched
     }
+
+    fn is_invalid(&self) -> bool {
+        self.internal.is_invalid()
+    }
+
+    fn relayout(&mut self) {
+        self.internal.relayout();
+        if self.internal.stretched_w > 0.0 {
+            self.stretch_children();
+        }
+        self.layout();
+    }
 }
 
 #[derive(Debug, Default)]
@@ -1077,16 +2290,41 @@ impl LayersLayout {
         self
     }
 
-    pub fn add(&mut self, mut widget: Box<dyn Widget>) {
-        let rect = widget.rect();
-        widget.set_pos(self.internal.rect.point());
-        self.internal.widgets.push(widget);
-        if self.internal.rect.h < rect.h {
-            self.internal.rect.h = rect.h;
-        }
-        if self.internal.rect.w < rect.w {
-            self.internal.rect.w = rect.w;
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.internal.padding = padding;
+        self.layout();
+        self
+    }
+
+    /// Aligns the smaller children.
+    pub fn with_align(mut self, align: Anchor) -> Self {
+        self.internal.align = align;
+        self.layout();
+        self
+    }
+
+    pub fn add(&mut self, widget: Box<dyn Widget>) {
+        self.internal.push(widget);
+        self.layout();
+    }
+
+    fn layout(&mut self) {
+        let layout = &mut self.internal;
+        let padding = layout.padding;
+        let inner = layout.max_child_size();
+        let w = (inner.x + padding * 2.0).max(layout.stretched_w);
+        let inner_w = w - padding * 2.0;
+        let origin = layout.rect.point() + Vec2::new(padding, padding);
+        for widget in &mut layout.widgets {
+            let rect = widget.rect();
+            let offset = Vec2::new(
+                layout.align.0.offset(inner_w - rect.w),
+                layout.align.1.offset(inner.y - rect.h),
+            );
+            widget.set_pos(origin + offset);
         }
+        layout.rect.w = w;
+        layout.rect.h = inner.y + padding * 2.0;
     }
 }
 
@@ -1095,6 +2333,263 @@ impl Widget for LayersLayout {
         self.internal.draw()
     }
 
+    fn draw_overlay(&self) {
+        self.internal.draw_overlay()
+    }
+
+    fn click(&self, pos: Vec2) {
+        self.internal.click(pos);
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        self.internal.move_mouse(pos);
+    }
+
+    fn press(&mut self, pos: Vec2) {
+        self.internal.press(pos);
+    }
+
+    fn release(&mut self, pos: Vec2) {
+        self.internal.release(pos);
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) {
+        self.internal.scroll(pos, delta);
+    }
+
+    fn focusables(&self) -> usize {
+        self.internal.focusables()
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.internal.set_focus(index);
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        self.internal.focused_rect()
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        self.internal.focusable_at(pos)
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        self.internal.press_key(key)
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        self.internal.type_char(c)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.internal.set_theme(theme);
+    }
+
+    fn rect(&self) -> Rect {
+        self.internal.rect()
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.internal.set_pos(pos);
+    }
+
+    fn can_stretch(&self) -> bool {
+        self.internal.can_stretch()
+    }
+
+    fn stretch(&mut self, width: f32) -> StretchStatus {
+        let status = self.internal.stretch(width);
+        self.layout();
+        status
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.internal.is_invalid()
+    }
+
+    fn relayout(&mut self) {
+        self.internal.relayout();
+        if self.internal.stretched_w > 0.0 {
+            self.internal.stretch_children();
+        }
+        self.layout();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GridCell {
+    row: usize,
+    col: usize,
+    rows: usize,
+    cols: usize,
+}
+
+/// Sizes of the grid's rows or columns that fit the items given as
+/// `(first index, span, size)`. The spanning items only widen the tracks
+/// that are too narrow for them.
+fn grid_tracks(items: &[(usize, usize, f32)], spacing: f32) -> Vec<f32> {
+    let count = items
+        .iter()
+        .map(|&(i, span, _)| i + span)
+        .max()
+        .unwrap_or(0);
+    let mut sizes = vec![0.0; count];
+    for &(i, _, size) in items.iter().filter(|&&(_, span, _)| span == 1) {
+        sizes[i] = f32::max(sizes[i], size);
+    }
+    for &(i, span, size) in items.iter().filter(|&&(_, span, _)| span > 1) {
+        let tracks = &mut sizes[i..i + span];
+        let taken = tracks.iter().sum::<f32>() + spacing * (span - 1) as f32;
+        if size > taken {
+            let additional = (size - taken) / span as f32;
+            for track in tracks {
+                *track += additional;
+            }
+        }
+    }
+    sizes
+}
+
+/// Starts of the tracks relative to the first one.
+fn grid_offsets(sizes: &[f32], spacing: f32) -> Vec<f32> {
+    let mut offset = 0.0;
+    let mut offsets = Vec::with_capacity(sizes.len());
+    for size in sizes {
+        offsets.push(offset);
+        offset += size + spacing;
+    }
+    offsets
+}
+
+/// Places the children into the cells of a table, a child may span several cells.
+///
+/// The stretchable children are stretched to their cells' width.
+#[derive(Debug, Default)]
+pub struct GridLayout {
+    internal: Layout,
+    cells: Vec<GridCell>,
+}
+
+impl GridLayout {
+    pub fn new() -> Self {
+        Self {
+            internal: Layout::new(),
+            cells: Vec::new(),
+        }
+    }
+
+    /// The extra width is divided between the columns.
+    pub fn stretchable(mut self, value: bool) -> Self {
+        self.internal.set_stretchable(value);
+        self
+    }
+
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.internal.spacing = spacing;
+        self.layout();
+        self
+    }
+
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.internal.padding = padding;
+        self.layout();
+        self
+    }
+
+    /// Aligns the children inside of their cells.
+    pub fn with_align(mut self, align: Anchor) -> Self {
+        self.internal.align = align;
+        self.layout();
+        self
+    }
+
+    pub fn add(&mut self, widget: Box<dyn Widget>, row: usize, col: usize) {
+        self.add_spanned(widget, row, col, 1, 1);
+    }
+
+    /// The child takes `rows` by `cols` cells starting from the given one.
+    pub fn add_spanned(
+        &mut self,
+        widget: Box<dyn Widget>,
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    ) {
+        let cell = GridCell {
+            row,
+            col,
+            rows: rows.max(1),
+            cols: cols.max(1),
+        };
+        self.cells.push(cell);
+        self.internal.push(widget);
+        self.layout();
+    }
+
+    fn layout(&mut self) {
+        let layout = &mut self.internal;
+        let spacing = layout.spacing;
+        let padding = layout.padding;
+        let cells_and_rects: Vec<_> = self
+            .cells
+            .iter()
+            .zip(&layout.widgets)
+            .map(|(&cell, widget)| (cell, widget.rect()))
+            .collect();
+        let col_items: Vec<_> = cells_and_rects
+            .iter()
+            .map(|&(cell, rect)| (cell.col, cell.cols, rect.w))
+            .collect();
+        let row_items: Vec<_> = cells_and_rects
+            .iter()
+            .map(|&(cell, rect)| (cell.row, cell.rows, rect.h))
+            .collect();
+        let mut col_sizes = grid_tracks(&col_items, spacing);
+        let row_sizes = grid_tracks(&row_items, spacing);
+        let total = |sizes: &[f32]| {
+            let gaps = spacing * sizes.len().saturating_sub(1) as f32;
+            sizes.iter().sum::<f32>() + gaps + padding * 2.0
+        };
+        let w = total(&col_sizes);
+        if layout.stretched_w > w && !col_sizes.is_empty() {
+            let additional = (layout.stretched_w - w) / col_sizes.len() as f32;
+            for size in &mut col_sizes {
+                *size += additional;
+            }
+        }
+        let xs = grid_offsets(&col_sizes, spacing);
+        let ys = grid_offsets(&row_sizes, spacing);
+        let origin = layout.rect.point() + Vec2::new(padding, padding);
+        for (cell, widget) in self.cells.iter().zip(&mut layout.widgets) {
+            let last_col = cell.col + cell.cols - 1;
+            let last_row = cell.row + cell.rows - 1;
+            let cell_w = xs[last_col] + col_sizes[last_col] - xs[cell.col];
+            let cell_h = ys[last_row] + row_sizes[last_row] - ys[cell.row];
+            if widget.can_stretch() {
+                widget.stretch(cell_w);
+            }
+            let rect = widget.rect();
+            let offset = Vec2::new(
+                xs[cell.col] + layout.align.0.offset(cell_w - rect.w),
+                ys[cell.row] + layout.align.1.offset(cell_h - rect.h),
+            );
+            widget.set_pos(origin + offset);
+        }
+        layout.rect.w = total(&col_sizes);
+        layout.rect.h = total(&row_sizes);
+    }
+}
+
+impl Widget for GridLayout {
+    fn draw(&self) {
+        self.internal.draw()
+    }
+
+    fn draw_overlay(&self) {
+        self.internal.draw_overlay()
+    }
+
     fn click(&self, pos: Vec2) {
         self.internal.click(pos);
     }
@@ -1103,6 +2598,46 @@ impl Widget for LayersLayout {
         self.internal.move_mouse(pos);
     }
 
+    fn press(&mut self, pos: Vec2) {
+        self.internal.press(pos);
+    }
+
+    fn release(&mut self, pos: Vec2) {
+        self.internal.release(pos);
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) {
+        self.internal.scroll(pos, delta);
+    }
+
+    fn focusables(&self) -> usize {
+        self.internal.focusables()
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.internal.set_focus(index);
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        self.internal.focused_rect()
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        self.internal.focusable_at(pos)
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        self.internal.press_key(key)
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        self.internal.type_char(c)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.internal.set_theme(theme);
+    }
+
     fn rect(&self) -> Rect {
         self.internal.rect()
     }
@@ -1116,6 +2651,1836 @@ impl Widget for LayersLayout {
     }
 
     fn stretch(&mut self, width: f32) -> StretchStatus {
-        self.internal.stretch(width)
+        if let Some(status) = stretch_checks(self, width) {
+            return status;
+        }
+        self.internal.stretched_w = width;
+        self.layout();
+        StretchStatus::Stretched
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.internal.is_invalid()
+    }
+
+    /// The stretchable children are stretched to their cells again.
+    fn relayout(&mut self) {
+        self.internal.relayout();
+        self.layout();
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    start_y: f32,
+    start_offset: f32,
+    is_thumb: bool,
+}
+
+/// Clips its child to a fixed rect and scrolls it vertically
+/// with the mouse wheel, dragging or the scrollbar.
+#[derive(Debug)]
+pub struct ScrollArea {
+    widget: Box<dyn Widget>,
+    rect: Rect,
+    offset: f32,
+    track: Sprite,
+    thumb: Sprite,
+    drag: Option<Drag>,
+}
+
+impl ScrollArea {
+    /// Only the size of the rect matters, the scrollbar is placed inside of it.
+    pub fn new(widget: Box<dyn Widget>, rect: Rect) -> Self {
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            ..rect
+        };
+        let track_rect = Rect::new(0.0, 0.0, SCROLLBAR_WIDTH, rect.h);
+        let track = make_rect(track_rect, Palette::default().bg);
+        let thumb = Self::make_thumb(rect.h, widget.rect().h);
+        let mut this = Self {
+            widget,
+            rect,
+            offset: 0.0,
+            track,
+            thumb,
+            drag: None,
+        };
+        this.layout();
+        this
+    }
+
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    pub fn scroll_to(&mut self, offset: f32) {
+        self.offset = offset.clamp(0.0, self.max_offset());
+        self.layout();
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll_to(self.max_offset());
+    }
+
+    fn max_offset(&self) -> f32 {
+        (self.widget.rect().h - self.rect.h).max(0.0)
+    }
+
+    fn is_scrollable(&self) -> bool {
+        self.max_offset() > 0.0
+    }
+
+    /// The part of the area that shows the child.
+    fn view_rect(&self) -> Rect {
+        Rect {
+            w: self.rect.w - SCROLLBAR_WIDTH,
+            ..self.rect
+        }
+    }
+
+    fn make_thumb(visible_h: f32, content_h: f32) -> Sprite {
+        let h = if content_h > visible_h {
+            (visible_h * visible_h / content_h).max(SCROLLBAR_WIDTH)
+        } else {
+            visible_h
+        };
+        make_rect(
+            Rect::new(0.0, 0.0, SCROLLBAR_WIDTH, h),
+            Palette::default().border,
+        )
+    }
+
+    fn layout(&mut self) {
+        let pos = self.rect.point();
+        self.widget.set_pos(pos - Vec2::new(0.0, self.offset));
+        let track_pos = pos + Vec2::new(self.rect.w - SCROLLBAR_WIDTH, 0.0);
+        self.track.set_pos(track_pos);
+        let max_offset = self.max_offset();
+        let thumb_y = if max_offset > 0.0 {
+            self.offset / max_offset * (self.rect.h - self.thumb.rect().h)
+        } else {
+            0.0
+        };
+        self.thumb.set_pos(track_pos + Vec2::new(0.0, thumb_y));
+    }
+}
+
+impl Widget for ScrollArea {
+    fn draw(&self) {
+        push_clip(self.view_rect());
+        self.widget.draw();
+        pop_clip();
+        if self.is_scrollable() {
+            self.track.draw();
+            self.thumb.draw();
+        }
+    }
+
+    fn draw_overlay(&self) {
+        self.widget.draw_overlay();
+    }
+
+    fn click(&self, pos: Vec2) {
+        if self.view_rect().contains(pos) {
+            self.widget.click(pos);
+        }
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        if let Some(drag) = self.drag {
+            let dy = pos.y - drag.start_y;
+            let offset = if drag.is_thumb {
+                let free_h = self.rect.h - self.thumb.rect().h;
+                drag.start_offset + dy * self.max_offset() / free_h
+            } else {
+                drag.start_offset - dy
+            };
+            self.scroll_to(offset);
+        }
+        if self.view_rect().contains(pos) {
+            self.widget.move_mouse(pos);
+        } else {
+            // Unhover the children hidden under the clip rect.
+            self.widget
+                .move_mouse(Vec2::new(f32::INFINITY, f32::INFINITY));
+        }
+    }
+
+    fn press(&mut self, pos: Vec2) {
+        if !self.rect.contains(pos) {
+            return;
+        }
+        if self.view_rect().contains(pos) {
+            self.widget.press(pos);
+        }
+        if !self.is_scrollable() {
+            return;
+        }
+        let is_thumb = self.thumb.rect().contains(pos);
+        if is_thumb || self.view_rect().contains(pos) {
+            self.drag = Some(Drag {
+                start_y: pos.y,
+                start_offset: self.offset,
+                is_thumb,
+            });
+        } else if pos.y < self.thumb.rect().y {
+            self.scroll_to(self.offset - self.rect.h);
+        } else {
+            self.scroll_to(self.offset + self.rect.h);
+        }
+    }
+
+    fn release(&mut self, pos: Vec2) {
+        self.drag = None;
+        self.widget.release(pos);
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) {
+        if !self.rect.contains(pos) {
+            return;
+        }
+        if self.is_scrollable() {
+            self.scroll_to(self.offset - delta * self.rect.h * SCROLL_STEP_K);
+        } else {
+            self.widget.scroll(pos, delta);
+        }
+    }
+
+    fn focusables(&self) -> usize {
+        self.widget.focusables()
+    }
+
+    /// Scrolls to the newly focused widget if it's hidden.
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.widget.set_focus(index);
+        if let Some(focused) = self.widget.focused_rect() {
+            if focused.y < self.rect.y {
+                self.scroll_to(self.offset - (self.rect.y - focused.y));
+            } else if focused.bottom() > self.rect.bottom() {
+                self.scroll_to(self.offset + (focused.bottom() - self.rect.bottom()));
+            }
+        }
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        self.widget.focused_rect()
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        if self.view_rect().contains(pos) {
+            self.widget.focusable_at(pos)
+        } else {
+            None
+        }
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        self.widget.press_key(key)
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        self.widget.type_char(c)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.track.color = theme.colors.bg;
+        self.thumb.color = theme.colors.border;
+        self.widget.set_theme(theme);
+    }
+
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.rect.move_to(pos);
+        self.layout();
+    }
+
+    /// The area's size is fixed, but the child's one isn't.
+    fn is_invalid(&self) -> bool {
+        self.widget.is_invalid()
+    }
+
+    /// Resizes the thumb and keeps the offset inside of the new content.
+    fn relayout(&mut self) {
+        self.widget.relayout();
+        let color = self.thumb.color;
+        self.thumb = Self::make_thumb(self.rect.h, self.widget.rect().h).color(color);
+        self.scroll_to(self.offset);
+    }
+}
+
+/// Background and border colors of the interactive widgets.
+fn frame_colors(colors: &Palette, is_hovered: bool, is_focused: bool) -> (Color, Color) {
+    let bg = if is_hovered || is_focused {
+        colors.bg_highlighted
+    } else {
+        colors.bg
+    };
+    let border = if is_focused {
+        colors.focus
+    } else {
+        colors.border
+    };
+    (bg, border)
+}
+
+fn is_activation_key(key: KeyPress) -> bool {
+    matches!(key.code, KeyCode::Enter | KeyCode::KpEnter | KeyCode::Space)
+}
+
+/// A box with a label that sends its new state when toggled.
+pub struct Checkbox<Message: Clone> {
+    is_checked: bool,
+    is_hovered: bool,
+    is_focused: bool,
+    rect: Rect,
+    bg: Sprite,
+    border: Sprite,
+    mark: Sprite,
+    label: Sprite,
+    theme: Theme,
+    sender: Sender<Message>,
+    on_change: Box<dyn Fn(bool) -> Message>,
+}
+
+impl<Message: Clone + Debug> Checkbox<Message> {
+    pub fn new(
+        drawable: Drawable,
+        height: f32,
+        is_checked: bool,
+        sender: Sender<Message>,
+        on_change: impl Fn(bool) -> Message + 'static,
+    ) -> Self {
+        let box_size = height * LabelParam::default().drawable_k;
+        let box_rect = Rect::new(0.0, 0.0, box_size, box_size);
+        let border_k = ButtonParam::default().border_k;
+        let border = Button::<Message>::make_border(box_size, box_rect, border_k);
+        let mark_size = box_size / 2.0;
+        let mark_rect = Rect::new(0.0, 0.0, mark_size, mark_size);
+        let mark = make_rect(mark_rect, Palette::default().text);
+        let label = Sprite::new(drawable, box_size);
+        let rect = Rect::new(0.0, 0.0, box_size * 1.5 + label.rect().w, height);
+        let mut this = Self {
+            is_checked,
+            is_hovered: false,
+            is_focused: false,
+            rect,
+            bg: make_bg(box_rect),
+            border,
+            mark,
+            label,
+            theme: Theme::default(),
+            sender,
+            on_change: Box::new(on_change),
+        };
+        this.set_pos(Vec2::new(0.0, 0.0));
+        this
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.is_checked
+    }
+
+    /// Doesn't send any messages.
+    pub fn set_checked(&mut self, value: bool) {
+        self.is_checked = value;
+    }
+
+    fn toggle(&mut self) {
+        self.is_checked = !self.is_checked;
+        let message = (self.on_change)(self.is_checked);
+        self.sender.send(message).unwrap();
+    }
+
+    fn update_highlight(&mut self) {
+        let (bg, border) = frame_colors(&self.theme.colors, self.is_hovered, self.is_focused);
+        self.bg.color = bg;
+        self.border.color = border;
+    }
+}
+
+impl<Message: Clone> Debug for Checkbox<Message> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Checkbox")
+            .field("is_checked", &self.is_checked)
+            .field("rect", &self.rect)
+            .finish()
+    }
+}
+
+impl<Message: Clone + Debug> Widget for Checkbox<Message> {
+    fn draw(&self) {
+        self.bg.draw();
+        if self.is_checked {
+            self.mark.draw();
+        }
+        self.border.draw();
+        self.label.draw();
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        self.is_hovered = self.rect.contains(pos);
+        self.update_highlight();
+    }
+
+    fn press(&mut self, pos: Vec2) {
+        if self.rect.contains(pos) {
+            self.toggle();
+        }
+    }
+
+    fn focusables(&self) -> usize {
+        1
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.is_focused = index.is_some();
+        self.update_highlight();
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        if self.is_focused {
+            Some(self.rect)
+        } else {
+            None
+        }
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        if !self.is_focused || !is_activation_key(key) {
+            return false;
+        }
+        self.toggle();
+        true
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        let box_rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            ..self.bg.rect()
+        };
+        let border_k = ButtonParam::default().border_k(theme);
+        self.border = Button::<Message>::make_border(box_rect.h, box_rect, border_k);
+        self.mark.color = theme.colors.text;
+        self.label.color = theme.colors.text;
+        self.update_highlight();
+        self.set_pos(self.rect.point());
+    }
+
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        let box_size = self.bg.rect().h;
+        let box_pos = pos + Vec2::new(0.0, (self.rect.h - box_size) / 2.0);
+        self.bg.set_pos(box_pos);
+        self.border.set_pos(box_pos);
+        let mark_offset = (box_size - self.mark.rect().w) / 2.0;
+        self.mark
+            .set_pos(box_pos + Vec2::new(mark_offset, mark_offset));
+        self.label.set_pos(box_pos + Vec2::new(box_size * 1.5, 0.0));
+        self.rect.move_to(pos);
+    }
+}
+
+/// Picks a value from a range by dragging the knob or with the arrow keys.
+///
+/// The value is continuous unless a step is set.
+pub struct Slider<Message: Clone> {
+    value: f32,
+    min: f32,
+    max: f32,
+    step: Option<f32>,
+    is_hovered: bool,
+    is_focused: bool,
+    is_dragging: bool,
+    rect: Rect,
+    bg: Sprite,
+    border: Sprite,
+    track: Sprite,
+    knob: Sprite,
+    theme: Theme,
+    sender: Sender<Message>,
+    on_change: Box<dyn Fn(f32) -> Message>,
+}
+
+impl<Message: Clone + Debug> Slider<Message> {
+    /// The range is `0.0..=1.0` by default.
+    pub fn new(
+        width: f32,
+        height: f32,
+        sender: Sender<Message>,
+        on_change: impl Fn(f32) -> Message + 'static,
+    ) -> Self {
+        let rect = Rect::new(0.0, 0.0, width, height);
+        let border_k = ButtonParam::default().border_k;
+        let border = Button::<Message>::make_border(height, rect, border_k);
+        let knob_w = height / 2.0;
+        let track_rect = Rect::new(0.0, 0.0, width - knob_w, height * 0.1);
+        let knob_rect = Rect::new(0.0, 0.0, knob_w, height * 0.7);
+        let mut this = Self {
+            value: 0.0,
+            min: 0.0,
+            max: 1.0,
+            step: None,
+            is_hovered: false,
+            is_focused: false,
+            is_dragging: false,
+            rect,
+            bg: make_bg(rect),
+            border,
+            track: make_rect(track_rect, Palette::default().text_inactive),
+            knob: make_rect(knob_rect, Palette::default().border),
+            theme: Theme::default(),
+            sender,
+            on_change: Box::new(on_change),
+        };
+        this.layout();
+        this
+    }
+
+    /// The bounds are swapped if `min` is greater than `max`.
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.min = min.min(max);
+        self.max = max.max(min);
+        self.set_value(self.value);
+        self
+    }
+
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = Some(step);
+        self.set_value(self.value);
+        self
+    }
+
+    pub fn with_value(mut self, value: f32) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Clamps and rounds the value to the step, doesn't send any messages.
+    pub fn set_value(&mut self, value: f32) {
+        let value = value.clamp(self.min, self.max);
+        self.value = match self.step {
+            Some(step) if step > 0.0 => {
+                let steps = ((value - self.min) / step).round();
+                (self.min + steps * step).min(self.max)
+            }
+            _ => value,
+        };
+        self.layout();
+    }
+
+    fn change_value(&mut self, value: f32) {
+        let old_value = self.value;
+        self.set_value(value);
+        if (self.value - old_value).abs() > f32::EPSILON {
+            let message = (self.on_change)(self.value);
+            self.sender.send(message).unwrap();
+        }
+    }
+
+    /// The value of the knob's position closest to the point.
+    fn value_at(&self, pos: Vec2) -> f32 {
+        let track = self.track.rect();
+        let k = ((pos.x - track.x) / track.w).clamp(0.0, 1.0);
+        self.min + k * (self.max - self.min)
+    }
+
+    fn layout(&mut self) {
+        let knob = self.knob.rect();
+        let track_pos =
+            self.rect.point() + Vec2::new(knob.w / 2.0, (self.rect.h - self.track.rect().h) / 2.0);
+        self.track.set_pos(track_pos);
+        let range = self.max - self.min;
+        let k = if range > 0.0 {
+            (self.value - self.min) / range
+        } else {
+            0.0
+        };
+        let knob_x = self.rect.x + k * self.track.rect().w;
+        let knob_y = self.rect.y + (self.rect.h - knob.h) / 2.0;
+        self.knob.set_pos(Vec2::new(knob_x, knob_y));
+        self.bg.set_pos(self.rect.point());
+        self.border.set_pos(self.rect.point());
+    }
+
+    fn update_highlight(&mut self) {
+        let (bg, border) = frame_colors(&self.theme.colors, self.is_hovered, self.is_focused);
+        self.bg.color = bg;
+        self.border.color = border;
+    }
+}
+
+impl<Message: Clone> Debug for Slider<Message> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Slider")
+            .field("value", &self.value)
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .field("step", &self.step)
+            .field("rect", &self.rect)
+            .finish()
+    }
+}
+
+impl<Message: Clone + Debug> Widget for Slider<Message> {
+    fn draw(&self) {
+        self.bg.draw();
+        self.track.draw();
+        self.knob.draw();
+        self.border.draw();
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        self.is_hovered = self.rect.contains(pos);
+        self.update_highlight();
+        if self.is_dragging {
+            self.change_value(self.value_at(pos));
+        }
+    }
+
+    fn press(&mut self, pos: Vec2) {
+        if self.rect.contains(pos) {
+            self.is_dragging = true;
+            self.change_value(self.value_at(pos));
+        }
+    }
+
+    fn release(&mut self, _: Vec2) {
+        self.is_dragging = false;
+    }
+
+    fn focusables(&self) -> usize {
+        1
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.is_focused = index.is_some();
+        self.update_highlight();
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        if self.is_focused {
+            Some(self.rect)
+        } else {
+            None
+        }
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        if !self.is_focused {
+            return false;
+        }
+        let step = self.step.unwrap_or((self.max - self.min) / 10.0);
+        match key.code {
+            KeyCode::Left => self.change_value(self.value - step),
+            KeyCode::Right => self.change_value(self.value + step),
+            KeyCode::Home => self.change_value(self.min),
+            KeyCode::End => self.change_value(self.max),
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            ..self.rect
+        };
+        let border_k = ButtonParam::default().border_k(theme);
+        self.border = Button::<Message>::make_border(rect.h, rect, border_k);
+        self.track.color = theme.colors.text_inactive;
+        self.knob.color = theme.colors.border;
+        self.update_highlight();
+        self.layout();
+    }
+
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.rect.move_to(pos);
+        self.layout();
+    }
+}
+
+/// Cycles through the labeled values with its arrows or the keys.
+pub struct Selector<Message: Clone, T: Clone> {
+    options: Vec<(Sprite, T)>,
+    index: usize,
+    is_hovered: bool,
+    is_focused: bool,
+    rect: Rect,
+    bg: Sprite,
+    border: Sprite,
+    prev: Sprite,
+    next: Sprite,
+    theme: Theme,
+    sender: Sender<Message>,
+    on_change: Box<dyn Fn(T) -> Message>,
+}
+
+impl<Message: Clone + Debug, T: Clone + Debug> Selector<Message, T> {
+    /// The selector is as wide as needed for the longest label.
+    pub fn new(
+        font: Font,
+        height: f32,
+        options: Vec<(String, T)>,
+        index: usize,
+        sender: Sender<Message>,
+        on_change: impl Fn(T) -> Message + 'static,
+    ) -> Result<Self> {
+        if options.is_empty() {
+            return Err(Error::NoOptions);
+        }
+        let text_height = height * LabelParam::default().drawable_k;
+        let make_text = |text: String| Sprite::new(Drawable::text(text, font), text_height);
+        let options: Vec<_> = options
+            .into_iter()
+            .map(|(label, value)| (make_text(label), value))
+            .collect();
+        let prev = make_text("<".into());
+        let next = make_text(">".into());
+        let labels_w = options
+            .iter()
+            .fold(0.0, |w: f32, (sprite, _)| w.max(sprite.rect().w));
+        let padding = height - text_height;
+        let w = prev.rect().w + next.rect().w + labels_w + padding * 4.0;
+        let rect = Rect::new(0.0, 0.0, w, height);
+        let border_k = ButtonParam::default().border_k;
+        let border = Button::<Message>::make_border(height, rect, border_k);
+        let mut this = Self {
+            index: index.min(options.len() - 1),
+            options,
+            is_hovered: false,
+            is_focused: false,
+            rect,
+            bg: make_bg(rect),
+            border,
+            prev,
+            next,
+            theme: Theme::default(),
+            sender,
+            on_change: Box::new(on_change),
+        };
+        this.set_pos(Vec2::new(0.0, 0.0));
+        Ok(this)
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn value(&self) -> &T {
+        &self.options[self.index].1
+    }
+
+    /// Doesn't send any messages.
+    pub fn set_index(&mut self, index: usize) {
+        self.index = index.min(self.options.len() - 1);
+        self.set_pos(self.rect.point());
+    }
+
+    fn select(&mut self, index: usize) {
+        self.set_index(index);
+        let message = (self.on_change)(self.value().clone());
+        self.sender.send(message).unwrap();
+    }
+
+    fn select_prev(&mut self) {
+        let count = self.options.len();
+        self.select((self.index + count - 1) % count);
+    }
+
+    fn select_next(&mut self) {
+        self.select((self.index + 1) % self.options.len());
+    }
+
+    fn update_highlight(&mut self) {
+        let (bg, border) = frame_colors(&self.theme.colors, self.is_hovered, self.is_focused);
+        self.bg.color = bg;
+        self.border.color = border;
+    }
+}
+
+impl<Message: Clone, T: Clone + Debug> Debug for Selector<Message, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values: Vec<&T> = self.options.iter().map(|(_, value)| value).collect();
+        f.debug_struct("Selector")
+            .field("values", &values)
+            .field("index", &self.index)
+            .field("rect", &self.rect)
+            .finish()
+    }
+}
+
+impl<Message: Clone + Debug, T: Clone + Debug> Widget for Selector<Message, T> {
+    fn draw(&self) {
+        self.bg.draw();
+        self.prev.draw();
+        self.options[self.index].0.draw();
+        self.next.draw();
+        self.border.draw();
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        self.is_hovered = self.rect.contains(pos);
+        self.update_highlight();
+    }
+
+    /// The left half selects the previous value, the right one - the next.
+    fn press(&mut self, pos: Vec2) {
+        if !self.rect.contains(pos) {
+            return;
+        }
+        if pos.x < self.rect.x + self.rect.w / 2.0 {
+            self.select_prev();
+        } else {
+            self.select_next();
+        }
+    }
+
+    fn focusables(&self) -> usize {
+        1
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.is_focused = index.is_some();
+        self.update_highlight();
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        if self.is_focused {
+            Some(self.rect)
+        } else {
+            None
+        }
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        if !self.is_focused {
+            return false;
+        }
+        match key.code {
+            KeyCode::Left => self.select_prev(),
+            KeyCode::Right => self.select_next(),
+            _ if is_activation_key(key) => self.select_next(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            ..self.rect
+        };
+        let border_k = ButtonParam::default().border_k(theme);
+        self.border = Button::<Message>::make_border(rect.h, rect, border_k);
+        for (sprite, _) in &mut self.options {
+            sprite.color = theme.colors.text;
+        }
+        self.prev.color = theme.colors.text;
+        self.next.color = theme.colors.text;
+        self.update_highlight();
+        self.set_pos(self.rect.point());
+    }
+
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.rect.move_to(pos);
+        self.bg.set_pos(pos);
+        self.border.set_pos(pos);
+        let text_h = self.prev.rect().h;
+        let padding = self.rect.h - text_h;
+        let y = pos.y + padding / 2.0;
+        self.prev.set_pos(Vec2::new(pos.x + padding, y));
+        let next_x = self.rect.right() - padding - self.next.rect().w;
+        self.next.set_pos(Vec2::new(next_x, y));
+        let label = &mut self.options[self.index].0;
+        let label_x = pos.x + (self.rect.w - label.rect().w) / 2.0;
+        label.set_pos(Vec2::new(label_x, y));
+    }
+}
+
+/// A styled piece of a [RichText].
+#[derive(Debug, Clone)]
+pub enum Span {
+    Text {
+        text: String,
+
+        /// The theme's text color is used without it.
+        color: Option<Color>,
+
+        is_bold: bool,
+    },
+
+    /// An icon as high as the text.
+    Icon(Texture2D),
+}
+
+impl Span {
+    pub fn text(text: impl Into<String>) -> Self {
+        Span::Text {
+            text: text.into(),
+            color: None,
+            is_bold: false,
+        }
+    }
+
+    pub fn icon(texture: Texture2D) -> Self {
+        Span::Icon(texture)
+    }
+
+    /// Does nothing for the icons.
+    pub fn with_color(mut self, new_color: Color) -> Self {
+        if let Span::Text { ref mut color, .. } = self {
+            *color = Some(new_color);
+        }
+        self
+    }
+
+    /// Uses the bold font of the [RichText], does nothing for the icons.
+    pub fn bold(mut self) -> Self {
+        if let Span::Text {
+            ref mut is_bold, ..
+        } = self
+        {
+            *is_bold = true;
+        }
+        self
+    }
+}
+
+/// Width of a space measured between two letters,
+/// as a lone space may have no width.
+fn space_width(font: Font, height: f32) -> f32 {
+    let width = |text: &str| Sprite::new(Drawable::text(text, font), height).rect().w;
+    width("a a") - width("aa")
+}
+
+#[derive(Debug)]
+struct RichTextItem {
+    sprite: Sprite,
+    x: f32,
+    line: usize,
+}
+
+/// Word wrapping state of [RichText]'s layout.
+struct Lines {
+    max_width: f32,
+    items: Vec<RichTextItem>,
+    widths: Vec<f32>,
+
+    /// Width of the spaces before the next item.
+    space: f32,
+}
+
+impl Lines {
+    fn new(max_width: f32) -> Self {
+        Self {
+            max_width,
+            items: Vec::new(),
+            widths: vec![0.0],
+            space: 0.0,
+        }
+    }
+
+    fn current(&self) -> usize {
+        self.widths.len() - 1
+    }
+
+    fn new_line(&mut self) {
+        self.widths.push(0.0);
+        self.space = 0.0;
+    }
+
+    fn add_space(&mut self, width: f32) {
+        self.space += width;
+    }
+
+    fn add(&mut self, sprite: Sprite) {
+        let w = sprite.rect().w;
+        let line_w = self.widths[self.current()];
+        if line_w > 0.0 && line_w + self.space + w > self.max_width {
+            self.new_line();
+        }
+        let line = self.current();
+        // The spaces at the lines' starts are skipped.
+        let x = if self.widths[line] > 0.0 {
+            self.widths[line] + self.space
+        } else {
+            0.0
+        };
+        self.items.push(RichTextItem { sprite, x, line });
+        self.widths[line] = x + w;
+        self.space = 0.0;
+    }
+}
+
+/// Multi-line text wrapped by words to the max width.
+///
+/// `'\n'` starts a new line, the lines are aligned inside the widest one.
+#[derive(Debug)]
+pub struct RichText {
+    spans: Vec<Span>,
+    font: Font,
+    bold_font: Option<Font>,
+    line_height: f32,
+    max_width: f32,
+    align: HAnchor,
+    is_stretchable: bool,
+    text_color: Color,
+    items: Vec<RichTextItem>,
+    line_widths: Vec<f32>,
+    rect: Rect,
+
+    /// The spans have been replaced since the last layout.
+    is_invalid: bool,
+}
+
+impl RichText {
+    pub fn new(spans: Vec<Span>, font: Font, line_height: f32, max_width: f32) -> Self {
+        let mut this = Self {
+            spans,
+            font,
+            bold_font: None,
+            line_height,
+            max_width,
+            align: HAnchor::Left,
+            is_stretchable: false,
+            text_color: Palette::default().text,
+            items: Vec::new(),
+            line_widths: Vec::new(),
+            rect: Rect::default(),
+            is_invalid: false,
+        };
+        this.layout();
+        this
+    }
+
+    /// Without it the bold spans use the regular font.
+    pub fn with_bold_font(mut self, font: Font) -> Self {
+        self.bold_font = Some(font);
+        self.layout();
+        self
+    }
+
+    pub fn with_align(mut self, align: HAnchor) -> Self {
+        self.align = align;
+        self.layout();
+        self
+    }
+
+    pub fn stretchable(mut self, value: bool) -> Self {
+        self.set_stretchable(value);
+        self
+    }
+
+    pub fn set_stretchable(&mut self, value: bool) {
+        self.is_stretchable = value;
+    }
+
+    /// Replaces the text, the widget's size may change.
+    pub fn set_spans(&mut self, spans: Vec<Span>) {
+        self.spans = spans;
+        self.layout();
+        self.is_invalid = true;
+    }
+
+    fn text_height(&self) -> f32 {
+        self.line_height * LabelParam::default().drawable_k
+    }
+
+    fn layout(&mut self) {
+        let text_height = self.text_height();
+        let mut lines = Lines::new(self.max_width);
+        for span in &self.spans {
+            match *span {
+                Span::Icon(texture) => {
+                    lines.add(Sprite::new(Drawable::Texture(texture), text_height));
+                }
+                Span::Text {
+                    ref text,
+                    color,
+                    is_bold,
+                } => {
+                    let font = match self.bold_font {
+                        Some(bold_font) if is_bold => bold_font,
+                        _ => self.font,
+                    };
+                    let space_width = space_width(font, text_height);
+                    for (i, text_line) in text.split('\n').enumerate() {
+                        if i != 0 {
+                            lines.new_line();
+                        }
+                        for (j, word) in text_line.split(' ').enumerate() {
+                            if j != 0 {
+                                lines.add_space(space_width);
+                            }
+                            if !word.is_empty() {
+                                let drawable = Drawable::text(word, font);
+                                let color = color.unwrap_or(self.text_color);
+                                lines.add(Sprite::new(drawable, text_height).color(color));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let w = lines.widths.iter().fold(0.0, |a: f32, &b| a.max(b));
+        let h = lines.widths.len() as f32 * self.line_height;
+        self.items = lines.items;
+        self.line_widths = lines.widths;
+        let pos = self.rect.point();
+        self.rect = Rect::new(pos.x, pos.y, w, h);
+        self.set_pos(pos);
+    }
+}
+
+impl Widget for RichText {
+    fn draw(&self) {
+        for item in &self.items {
+            item.sprite.draw();
+        }
+    }
+
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.rect.move_to(pos);
+        let padding = (self.line_height - self.text_height()) / 2.0;
+        for item in &mut self.items {
+            let offset = self.align.offset(self.rect.w - self.line_widths[item.line]);
+            let y = item.line as f32 * self.line_height + padding;
+            item.sprite.set_pos(pos + Vec2::new(offset + item.x, y));
+        }
+    }
+
+    fn can_stretch(&self) -> bool {
+        self.is_stretchable
+    }
+
+    fn stretch(&mut self, width: f32) -> StretchStatus {
+        if let Some(status) = stretch_checks(self, width) {
+            return status;
+        }
+        self.rect.w = width;
+        self.set_pos(self.rect.point());
+        StretchStatus::Stretched
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.text_color = theme.colors.text;
+        self.layout();
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.is_invalid
+    }
+
+    fn relayout(&mut self) {
+        self.is_invalid = false;
+    }
+}
+
+/// Shows a floating panel when the cursor stays over the wrapped widget.
+///
+/// The panel is drawn by [Widget::draw_overlay], so it isn't covered
+/// by the widgets added after this one.
+#[derive(Debug)]
+pub struct Tooltip {
+    widget: Box<dyn Widget>,
+    content: Box<dyn Widget>,
+    bg: Sprite,
+    border: Sprite,
+
+    /// When the cursor has entered the widget, in seconds.
+    hover_start: Option<f64>,
+
+    is_shown: bool,
+}
+
+impl Tooltip {
+    pub fn new(widget: Box<dyn Widget>, content: Box<dyn Widget>) -> Self {
+        let colors = Palette::default();
+        let (bg, border) = Self::make_panel(content.rect(), colors.bg_highlighted, colors.border);
+        Self {
+            widget,
+            content,
+            bg,
+            border,
+            hover_start: None,
+            is_shown: false,
+        }
+    }
+
+    /// The background and the border around the content.
+    fn make_panel(content_rect: Rect, bg_color: Color, border_color: Color) -> (Sprite, Sprite) {
+        let rect = Rect::new(
+            0.0,
+            0.0,
+            content_rect.w + TOOLTIP_PADDING * 2.0,
+            content_rect.h + TOOLTIP_PADDING * 2.0,
+        );
+        let thickness = TOOLTIP_PADDING / 4.0;
+        let border =
+            Sprite::new(Drawable::LinesRect { rect, thickness }, rect.h).color(border_color);
+        (make_rect(rect, bg_color), border)
+    }
+
+    /// Lines of text with an optional icon to the left of them.
+    pub fn from_text(
+        widget: Box<dyn Widget>,
+        icon: Option<Drawable>,
+        text: &str,
+        font: Font,
+        line_height: f32,
+    ) -> Result<Self> {
+        let mut lines = VLayout::new();
+        for line in text.lines() {
+            let label = Label::new(Drawable::text(line, font), line_height)?;
+            lines.add(Box::new(label));
+        }
+        let content: Box<dyn Widget> = match icon {
+            Some(icon) => {
+                let icon_height = lines.rect().h.min(line_height * 2.0);
+                let mut layout = HLayout::new();
+                layout.add(Box::new(Label::new(icon, icon_height)?));
+                layout.add(Box::new(Spacer::new_horizontal(TOOLTIP_PADDING)));
+                layout.add(Box::new(lines));
+                Box::new(layout)
+            }
+            None => Box::new(lines),
+        };
+        Ok(Self::new(widget, content))
+    }
+
+    /// Puts the panel next to the cursor, but inside the screen.
+    fn place(&mut self, cursor: Vec2) {
+        let size = self.bg.rect().size();
+        let aspect_ratio = window::screen_width() / window::screen_height();
+        let mut pos = cursor + Vec2::new(TOOLTIP_OFFSET, TOOLTIP_OFFSET);
+        if pos.x + size.x > aspect_ratio {
+            pos.x = cursor.x - TOOLTIP_OFFSET - size.x;
+        }
+        if pos.y + size.y > 1.0 {
+            pos.y = cursor.y - TOOLTIP_OFFSET - size.y;
+        }
+        pos.x = pos.x.min(aspect_ratio - size.x).max(-aspect_ratio);
+        pos.y = pos.y.min(1.0 - size.y).max(-1.0);
+        self.bg.set_pos(pos);
+        self.border.set_pos(pos);
+        self.content
+            .set_pos(pos + Vec2::new(TOOLTIP_PADDING, TOOLTIP_PADDING));
+    }
+}
+
+impl Widget for Tooltip {
+    fn draw(&self) {
+        self.widget.draw();
+    }
+
+    fn draw_overlay(&self) {
+        self.widget.draw_overlay();
+        if self.is_shown {
+            self.bg.draw();
+            self.content.draw();
+            self.border.draw();
+        }
+    }
+
+    fn click(&self, pos: Vec2) {
+        self.widget.click(pos);
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        self.widget.move_mouse(pos);
+        if !self.widget.rect().contains(pos) {
+            self.hover_start = None;
+            self.is_shown = false;
+            return;
+        }
+        let now = get_time();
+        let start = *self.hover_start.get_or_insert(now);
+        self.is_shown = now - start >= TOOLTIP_DELAY;
+        if self.is_shown {
+            self.place(pos);
+        }
+    }
+
+    /// Hides the panel until the cursor stays still again.
+    fn press(&mut self, pos: Vec2) {
+        self.widget.press(pos);
+        if self.hover_start.is_some() {
+            self.hover_start = Some(get_time());
+            self.is_shown = false;
+        }
+    }
+
+    fn release(&mut self, pos: Vec2) {
+        self.widget.release(pos);
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) {
+        self.widget.scroll(pos, delta);
+    }
+
+    fn focusables(&self) -> usize {
+        self.widget.focusables()
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.widget.set_focus(index);
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        self.widget.focused_rect()
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        self.widget.focusable_at(pos)
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        self.widget.press_key(key)
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        self.widget.type_char(c)
+    }
+
+    /// Also applies the theme to the panel's content.
+    fn set_theme(&mut self, theme: &Theme) {
+        self.widget.set_theme(theme);
+        self.content.set_theme(theme);
+        self.bg.color = theme.colors.bg_highlighted;
+        self.border.color = theme.colors.border;
+    }
+
+    fn rect(&self) -> Rect {
+        self.widget.rect()
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.widget.set_pos(pos);
+    }
+
+    fn can_stretch(&self) -> bool {
+        self.widget.can_stretch()
+    }
+
+    fn stretch(&mut self, width: f32) -> StretchStatus {
+        self.widget.stretch(width)
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.widget.is_invalid() || self.content.is_invalid()
+    }
+
+    /// The panel is resized to the new content.
+    fn relayout(&mut self) {
+        if self.widget.is_invalid() {
+            self.widget.relayout();
+        }
+        if self.content.is_invalid() {
+            self.content.relayout();
+            let (bg, border) =
+                Self::make_panel(self.content.rect(), self.bg.color, self.border.color);
+            self.bg = bg;
+            self.border = border;
+            self.is_shown = false;
+        }
+    }
+}
+
+/// Keeps its own theme for the child when the [Gui]'s one changes.
+#[derive(Debug)]
+pub struct Themed {
+    widget: Box<dyn Widget>,
+}
+
+impl Themed {
+    pub fn new(mut widget: Box<dyn Widget>, theme: &Theme) -> Self {
+        widget.set_theme(theme);
+        Self { widget }
+    }
+}
+
+impl Widget for Themed {
+    fn draw(&self) {
+        self.widget.draw();
+    }
+
+    fn draw_overlay(&self) {
+        self.widget.draw_overlay();
+    }
+
+    fn click(&self, pos: Vec2) {
+        self.widget.click(pos);
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        self.widget.move_mouse(pos);
+    }
+
+    fn press(&mut self, pos: Vec2) {
+        self.widget.press(pos);
+    }
+
+    fn release(&mut self, pos: Vec2) {
+        self.widget.release(pos);
+    }
+
+    fn scroll(&mut self, pos: Vec2, delta: f32) {
+        self.widget.scroll(pos, delta);
+    }
+
+    fn focusables(&self) -> usize {
+        self.widget.focusables()
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.widget.set_focus(index);
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        self.widget.focused_rect()
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        self.widget.focusable_at(pos)
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        self.widget.press_key(key)
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        self.widget.type_char(c)
+    }
+
+    /// The parent's theme is ignored.
+    fn set_theme(&mut self, _: &Theme) {}
+
+    fn rect(&self) -> Rect {
+        self.widget.rect()
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.widget.set_pos(pos);
+    }
+
+    fn can_stretch(&self) -> bool {
+        self.widget.can_stretch()
+    }
+
+    fn stretch(&mut self, width: f32) -> StretchStatus {
+        self.widget.stretch(width)
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.widget.is_invalid()
+    }
+
+    fn relayout(&mut self) {
+        self.widget.relayout();
+    }
+}
+
+/// A single line text field.
+///
+/// The characters come through [Widget::type_char], so the input has
+/// to be focused with a click or the keyboard first.
+pub struct TextInput<Message: Clone> {
+    text: Vec<char>,
+    placeholder: String,
+    font: Font,
+    max_len: usize,
+    validator: Box<dyn Fn(&str) -> bool>,
+    on_submit: Box<dyn Fn(String) -> Message>,
+    sender: Sender<Message>,
+
+    /// Char index, same as `anchor` when nothing is selected.
+    caret: usize,
+
+    /// The selection's other end.
+    anchor: usize,
+
+    is_focused: bool,
+    is_dragging: bool,
+    is_valid: bool,
+
+    /// Shift of the text that keeps the caret visible.
+    scroll: f32,
+
+    rect: Rect,
+    text_height: f32,
+    sprite: Sprite,
+    bg: Sprite,
+    border: Sprite,
+    caret_sprite: Sprite,
+    selection_sprite: Option<Sprite>,
+    theme: Theme,
+}
+
+impl<Message: Clone + Debug> TextInput<Message> {
+    /// `on_submit` makes a message from the text when Enter is pressed.
+    pub fn new(
+        font: Font,
+        width: f32,
+        height: f32,
+        sender: Sender<Message>,
+        on_submit: impl Fn(String) -> Message + 'static,
+    ) -> Self {
+        let rect = Rect::new(0.0, 0.0, width, height);
+        let text_height = height * LabelParam::default().drawable_k;
+        let border_k = ButtonParam::default().border_k;
+        let border = Button::<Message>::make_border(height, rect, border_k);
+        let caret_rect = Rect::new(0.0, 0.0, height * CARET_WIDTH_K, text_height);
+        let mut this = Self {
+            text: Vec::new(),
+            placeholder: String::new(),
+            font,
+            max_len: usize::MAX,
+            validator: Box::new(|_| true),
+            on_submit: Box::new(on_submit),
+            sender,
+            caret: 0,
+            anchor: 0,
+            is_focused: false,
+            is_dragging: false,
+            is_valid: true,
+            scroll: 0.0,
+            rect,
+            text_height,
+            sprite: Sprite::new(Drawable::text("", font), text_height),
+            bg: make_bg(rect),
+            border,
+            caret_sprite: make_rect(caret_rect, Palette::default().text),
+            selection_sprite: None,
+            theme: Theme::default(),
+        };
+        this.update();
+        this
+    }
+
+    /// Shown in the empty input.
+    pub fn with_placeholder(mut self, text: impl Into<String>) -> Self {
+        self.placeholder = text.into();
+        self.update();
+        self
+    }
+
+    /// Maximum number of characters.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self.text.truncate(max_len);
+        self.move_caret(self.caret, false);
+        self
+    }
+
+    /// Invalid text is outlined and can't be submitted.
+    pub fn with_validator(mut self, validator: impl Fn(&str) -> bool + 'static) -> Self {
+        self.validator = Box::new(validator);
+        self.update();
+        self
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.set_text(text);
+        self
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.chars().take(self.max_len).collect();
+        self.move_caret(self.text.len(), false);
+    }
+
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+
+    fn selection(&self) -> Option<(usize, usize)> {
+        if self.caret == self.anchor {
+            None
+        } else {
+            Some((self.caret.min(self.anchor), self.caret.max(self.anchor)))
+        }
+    }
+
+    fn move_caret(&mut self, index: usize, is_selecting: bool) {
+        self.caret = index.min(self.text.len());
+        if !is_selecting {
+            self.anchor = self.caret;
+        }
+        self.update();
+    }
+
+    /// Returns `false` if nothing was selected.
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some((start, end)) => {
+                self.text.drain(start..end);
+                self.move_caret(start, false);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, c: char) {
+        self.delete_selection();
+        if self.text.len() < self.max_len {
+            self.text.insert(self.caret, c);
+            self.move_caret(self.caret + 1, false);
+        }
+    }
+
+    fn submit(&self) {
+        if self.is_valid {
+            let message = (self.on_submit)(self.text());
+            self.sender.send(message).unwrap();
+        }
+    }
+
+    fn text_width(&self, chars: &[char]) -> f32 {
+        let label: String = chars.iter().collect();
+        Sprite::new(Drawable::text(label, self.font), self.text_height)
+            .rect()
+            .w
+    }
+
+    /// The part of the input the text is drawn in.
+    fn text_rect(&self) -> Rect {
+        let padding = (self.rect.h - self.text_height) / 2.0;
+        Rect {
+            x: self.rect.x + padding,
+            y: self.rect.y + padding,
+            w: self.rect.w - padding * 2.0,
+            h: self.text_height,
+        }
+    }
+
+    /// Caret position closest to the point.
+    fn index_at(&self, pos: Vec2) -> usize {
+        let x = pos.x - self.text_rect().x + self.scroll;
+        let distance = |i: usize| (self.text_width(&self.text[..i]) - x).abs();
+        (0..=self.text.len())
+            .min_by(|&a, &b| distance(a).total_cmp(&distance(b)))
+            .unwrap_or(0)
+    }
+
+    /// Rebuilds the sprites after any change of the text, caret or focus.
+    fn update(&mut self) {
+        let text = self.text();
+        self.is_valid = (self.validator)(&text);
+        let colors = self.theme.colors;
+        let (label, color) = if self.text.is_empty() {
+            (self.placeholder.clone(), colors.text_inactive)
+        } else {
+            (text, colors.text)
+        };
+        self.sprite = Sprite::new(Drawable::text(label, self.font), self.text_height).color(color);
+        let text_rect = self.text_rect();
+        let caret_x = self.text_width(&self.text[..self.caret]);
+        if caret_x - self.scroll > text_rect.w {
+            self.scroll = caret_x - text_rect.w;
+        } else if caret_x < self.scroll {
+            self.scroll = caret_x;
+        }
+        let origin = text_rect.point() - Vec2::new(self.scroll, 0.0);
+        self.sprite.set_pos(origin);
+        self.caret_sprite.set_pos(origin + Vec2::new(caret_x, 0.0));
+        self.selection_sprite = self.selection().map(|(start, end)| {
+            let x = self.text_width(&self.text[..start]);
+            let w = self.text_width(&self.text[..end]) - x;
+            let rect = Rect::new(0.0, 0.0, w, self.text_height);
+            let mut sprite = make_rect(rect, colors.selection);
+            sprite.set_pos(origin + Vec2::new(x, 0.0));
+            sprite
+        });
+        self.bg.set_pos(self.rect.point());
+        self.bg.color = if self.is_focused {
+            colors.bg_highlighted
+        } else {
+            colors.bg
+        };
+        self.border.set_pos(self.rect.point());
+        self.border.color = if !self.is_valid {
+            colors.error
+        } else if self.is_focused {
+            colors.focus
+        } else {
+            colors.border
+        };
+    }
+}
+
+impl<Message: Clone> Debug for TextInput<Message> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TextInput")
+            .field("text", &self.text)
+            .field("caret", &self.caret)
+            .field("anchor", &self.anchor)
+            .field("is_focused", &self.is_focused)
+            .field("rect", &self.rect)
+            .finish()
+    }
+}
+
+impl<Message: Clone + Debug> Widget for TextInput<Message> {
+    fn draw(&self) {
+        self.bg.draw();
+        push_clip(self.text_rect());
+        if let Some(ref selection) = self.selection_sprite {
+            selection.draw();
+        }
+        self.sprite.draw();
+        if self.is_focused {
+            self.caret_sprite.draw();
+        }
+        pop_clip();
+        self.border.draw();
+    }
+
+    fn move_mouse(&mut self, pos: Vec2) {
+        if self.is_dragging {
+            let index = self.index_at(pos);
+            self.move_caret(index, true);
+        }
+    }
+
+    fn press(&mut self, pos: Vec2) {
+        if self.rect.contains(pos) {
+            self.is_dragging = true;
+            let index = self.index_at(pos);
+            self.move_caret(index, false);
+        }
+    }
+
+    fn release(&mut self, _: Vec2) {
+        self.is_dragging = false;
+    }
+
+    fn focusables(&self) -> usize {
+        1
+    }
+
+    fn set_focus(&mut self, index: Option<usize>) {
+        self.is_focused = index.is_some();
+        self.update();
+    }
+
+    fn focused_rect(&self) -> Option<Rect> {
+        if self.is_focused {
+            Some(self.rect)
+        } else {
+            None
+        }
+    }
+
+    fn focusable_at(&self, pos: Vec2) -> Option<usize> {
+        if self.rect.contains(pos) {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    fn press_key(&mut self, key: KeyPress) -> bool {
+        if !self.is_focused {
+            return false;
+        }
+        match key.code {
+            KeyCode::Left => {
+                let index = match self.selection() {
+                    Some((start, _)) if !key.shift => start,
+                    _ => self.caret.saturating_sub(1),
+                };
+                self.move_caret(index, key.shift);
+            }
+            KeyCode::Right => {
+                let index = match self.selection() {
+                    Some((_, end)) if !key.shift => end,
+                    _ => self.caret + 1,
+                };
+                self.move_caret(index, key.shift);
+            }
+            KeyCode::Home => self.move_caret(0, key.shift),
+            KeyCode::End => self.move_caret(self.text.len(), key.shift),
+            KeyCode::Backspace => {
+                if !self.delete_selection() && self.caret > 0 {
+                    self.text.remove(self.caret - 1);
+                    self.move_caret(self.caret - 1, false);
+                }
+            }
+            KeyCode::Delete => {
+                if !self.delete_selection() && self.caret < self.text.len() {
+                    self.text.remove(self.caret);
+                    self.update();
+                }
+            }
+            KeyCode::Enter | KeyCode::KpEnter => self.submit(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
+        if !self.is_focused || c.is_control() {
+            return false;
+        }
+        self.insert(c);
+        true
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            ..self.rect
+        };
+        let border_k = ButtonParam::default().border_k(theme);
+        self.border = Button::<Message>::make_border(rect.h, rect, border_k);
+        self.caret_sprite.color = theme.colors.text;
+        self.update();
+    }
+
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_pos(&mut self, pos: Vec2) {
+        self.rect.move_to(pos);
+        self.update();
     }
 }
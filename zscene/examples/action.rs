@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use mq::{
     camera::{set_camera, Camera2D},
@@ -8,7 +8,10 @@ use mq::{
     texture::{self, Texture2D},
     time, window,
 };
-use zscene::{self, action, Action, Boxed, Layer, Scene, Sprite};
+use zscene::{
+    self, action, Action, Atlas, AutotileRule, Boxed, Easing, EmitterParams, Layer,
+    ParticleEmitter, Scene, Sprite, TileLayer,
+};
 
 #[derive(Debug)]
 pub enum Err {
@@ -43,13 +46,19 @@ impl Layers {
 struct Assets {
     font: text::Font,
     texture: Texture2D,
+    tiles: Texture2D,
 }
 
 impl Assets {
     async fn load() -> Result<Self, Err> {
         let font = text::load_ttf_font("zscene/assets/Karla-Regular.ttf").await?;
         let texture = texture::load_texture("zscene/assets/fire.png").await?;
-        Ok(Self { font, texture })
+        let tiles = texture::load_texture("zscene/assets/tiles.png").await?;
+        Ok(Self {
+            font,
+            texture,
+            tiles,
+        })
     }
 }
 
@@ -71,6 +80,26 @@ impl State {
         }
     }
 
+    /// A grass field with a lake, the shores are picked by the autotiling.
+    fn add_lake(&mut self) {
+        let (w, h) = (12, 8);
+        let size = 2.0 / h as f32;
+        let atlas = Atlas::new(self.assets.tiles, 5, 4);
+        let mut tiles = TileLayer::new(atlas, w, h, Vec2::new(size, size));
+        tiles.set_pos(Vec2::new(-size * w as f32 / 2.0, -1.0));
+        let terrain: Vec<bool> = (0..w * h)
+            .map(|i| {
+                let (x, y) = (i % w, i / w);
+                (3..9).contains(&x) && (2..6).contains(&y) && (x, y) != (3, 2)
+            })
+            .collect();
+        let mut rules = HashMap::new();
+        rules.insert(false, AutotileRule::Fixed(0));
+        rules.insert(true, AutotileRule::Edges { first: 1 });
+        tiles.autotile(&terrain, &rules);
+        self.layers.bg.add_tile_layer(&tiles);
+    }
+
     fn action_demo_move(&self) -> Box<dyn Action> {
         let mut sprite = Sprite::from_texture(self.assets.texture, 0.5);
         sprite.set_pos(Vec2::new(0.0, -1.0));
@@ -78,7 +107,9 @@ impl State {
         let move_duration = Duration::from_millis(2_000);
         let action = action::Sequence::new(vec![
             action::Show::new(&self.layers.fg, &sprite).boxed(),
-            action::MoveBy::new(&sprite, delta, move_duration).boxed(),
+            action::MoveBy::new(&sprite, delta, move_duration)
+                .with_easing(Easing::QuadOut)
+                .boxed(),
         ]);
         action.boxed()
     }
@@ -105,6 +136,31 @@ impl State {
         ]);
         action.boxed()
     }
+
+    fn action_demo_particles(&mut self) -> Box<dyn Action> {
+        let params = EmitterParams {
+            rate: 40.0,
+            lifetime: Duration::from_millis(800),
+            lifetime_spread: 0.3,
+            spawn_radius: 0.05,
+            velocity: Vec2::new(0.0, -0.4),
+            velocity_spread: Vec2::new(0.1, 0.1),
+            color_start: Color::new(1.0, 0.8, 0.3, 1.0),
+            color_end: Color::new(1.0, 0.2, 0.0, 0.0),
+            scale_end: 0.3,
+            ..EmitterParams::new(self.assets.texture, 0.1)
+        };
+        let mut emitter = ParticleEmitter::new(params);
+        emitter.set_pos(Vec2::new(-0.5, 0.5));
+        self.layers.fg.add_emitter(&emitter);
+        let t = Duration::from_millis(2_000);
+        let action = action::Sequence::new(vec![
+            action::Emit::new(&emitter, t).boxed(),
+            action::Sleep::new(t / 2).boxed(),
+            action::Emit::burst(&emitter, 50).boxed(),
+        ]);
+        action.boxed()
+    }
 }
 
 fn update_aspect_ratio() {
@@ -118,10 +174,13 @@ fn update_aspect_ratio() {
 async fn main() {
     let assets = Assets::load().await.expect("Can't load assets");
     let mut state = State::new(assets);
+    state.add_lake();
     {
-        // Run two demo demo actions in parallel.
+        // Run the demo actions in parallel.
         state.scene.add_action(state.action_demo_move());
         state.scene.add_action(state.action_demo_show_hide());
+        let particles = state.action_demo_particles();
+        state.scene.add_action(particles);
     }
     loop {
         window::clear_background(BLACK);
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use mq::color::Color;
 
-use crate::{Action, Sprite};
+use crate::{easing::interpolate, Action, Sprite};
 
 #[derive(Debug)]
 pub struct ChangeColorTo {
@@ -53,13 +53,3 @@ impl Action for ChangeColorTo {
         self.progress >= self.duration
     }
 }
-
-fn interpolate(from: Color, to: Color, k: f32) -> Color {
-    let calc = |a, b| a + (b - a) * k;
-    Color {
-        r: calc(from.r, to.r),
-        g: calc(from.g, to.g),
-        b: calc(from.b, to.b),
-        a: calc(from.a, to.a),
-    }
-}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use crate::{Action, ParticleEmitter};
+
+/// Emits particles continuously for some time or spawns a burst of them.
+#[derive(Debug)]
+pub struct Emit {
+    emitter: ParticleEmitter,
+    burst: usize,
+    duration: Duration,
+    progress: Duration,
+
+    /// Began its emission and hasn't ended it yet.
+    is_emitting: bool,
+}
+
+impl Emit {
+    pub fn new(emitter: &ParticleEmitter, duration: Duration) -> Self {
+        Self {
+            emitter: emitter.clone(),
+            burst: 0,
+            duration,
+            progress: Duration::new(0, 0),
+            is_emitting: false,
+        }
+    }
+
+    pub fn burst(emitter: &ParticleEmitter, count: usize) -> Self {
+        Self {
+            emitter: emitter.clone(),
+            burst: count,
+            duration: Duration::new(0, 0),
+            progress: Duration::new(0, 0),
+            is_emitting: false,
+        }
+    }
+}
+
+impl Action for Emit {
+    fn begin(&mut self) {
+        if self.burst > 0 {
+            self.emitter.burst(self.burst);
+        }
+        if self.duration > Duration::new(0, 0) {
+            self.emitter.begin_emission();
+            self.is_emitting = true;
+        }
+    }
+
+    fn update(&mut self, dtime: Duration) {
+        self.progress += dtime;
+    }
+
+    fn end(&mut self) {
+        if self.is_emitting {
+            self.emitter.end_emission();
+            self.is_emitting = false;
+        }
+    }
+
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn is_finished(&self) -> bool {
+        self.progress >= self.duration
+    }
+}
+
+/// The canceled actions are dropped without being ended,
+/// so their emissions are ended here.
+impl Drop for Emit {
+    fn drop(&mut self) {
+        if self.is_emitting {
+            self.emitter.end_emission();
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use mq::math::Vec2;
 
-use crate::{Action, Sprite};
+use crate::{Action, Easing, Sprite};
 
 #[derive(Debug)]
 pub struct MoveBy {
@@ -10,6 +10,7 @@ pub struct MoveBy {
     duration: Duration,
     delta: Vec2,
     progress: Duration,
+    easing: Easing,
 }
 
 impl MoveBy {
@@ -19,8 +20,14 @@ impl MoveBy {
             delta,
             duration,
             progress: Duration::new(0, 0),
+            easing: Easing::Linear,
         }
     }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
 }
 
 impl Action for MoveBy {
@@ -29,11 +36,13 @@ impl Action for MoveBy {
         if dtime + self.progress > self.duration {
             dtime = self.duration - self.progress;
         }
-        let dtime_f = dtime.as_secs_f32();
         let duration_f = self.duration.as_secs_f32();
-        let new_pos = old_pos + self.delta * (dtime_f / duration_f);
-        self.sprite.set_pos(new_pos);
+        let old_k = self.easing.apply(self.progress.as_secs_f32() / duration_f);
         self.progress += dtime;
+        let new_k = self.easing.apply(self.progress.as_secs_f32() / duration_f);
+        // Moves by the difference only, so other actions can move the sprite too.
+        let new_pos = old_pos + self.delta * (new_k - old_k);
+        self.sprite.set_pos(new_pos);
     }
 
     fn duration(&self) -> Duration {
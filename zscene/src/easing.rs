@@ -0,0 +1,47 @@
+use mq::color::Color;
+
+/// Maps an action's linear progress (`0.0..=1.0`) to the eased one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+
+    /// Starts slowly and accelerates.
+    QuadIn,
+
+    /// Starts fast and decelerates.
+    QuadOut,
+
+    QuadInOut,
+
+    /// Overshoots the target a bit and settles back.
+    BackOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Easing::QuadInOut => -1.0 + (4.0 - 2.0 * t) * t,
+            Easing::BackOut => {
+                const K: f32 = 1.70158;
+                let t = t - 1.0;
+                t * t * ((K + 1.0) * t + K) + 1.0
+            }
+        }
+    }
+}
+
+/// Linear interpolation between the colors, `k` is in `0.0..=1.0`.
+pub(crate) fn interpolate(from: Color, to: Color, k: f32) -> Color {
+    let calc = |a, b| a + (b - a) * k;
+    Color {
+        r: calc(from.r, to.r),
+        g: calc(from.g, to.g),
+        b: calc(from.b, to.b),
+        a: calc(from.a, to.a),
+    }
+}
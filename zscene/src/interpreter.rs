@@ -0,0 +1,299 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    time::Duration,
+};
+
+use crate::Action;
+
+/// A big enough time step to finish any sane action in one update.
+const SKIP_STEP: Duration = Duration::from_secs(60 * 60);
+
+/// An action that isn't finished after this many skip steps is ended anyway.
+const SKIP_MAX_STEPS: usize = 100;
+
+/// How many of the latest canceled handles are remembered.
+const CANCELED_MEMORY: usize = 64;
+
+/// Identifies an action added to the [crate::Scene].
+///
+/// Actions forked by the action share its handle, so the handle
+/// is finished only when all of them are finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActionHandle(u64);
+
+struct Callback(Box<dyn FnOnce()>);
+
+impl fmt::Debug for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Callback").field(&"").finish()
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    handle: ActionHandle,
+    action: Box<dyn Action>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ActionInterpreter {
+    entries: Vec<Entry>,
+    tags: HashMap<ActionHandle, HashSet<String>>,
+    callbacks: HashMap<ActionHandle, Vec<Callback>>,
+
+    /// The latest canceled handles. Their callbacks are dropped,
+    /// even the ones added after the cancel.
+    canceled: VecDeque<ActionHandle>,
+
+    next_handle: u64,
+    time_scale: f32,
+    tag_time_scales: HashMap<String, f32>,
+    is_paused: bool,
+    is_step_requested: bool,
+}
+
+impl ActionInterpreter {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            tags: HashMap::new(),
+            callbacks: HashMap::new(),
+            canceled: VecDeque::with_capacity(CANCELED_MEMORY),
+            next_handle: 0,
+            time_scale: 1.0,
+            tag_time_scales: HashMap::new(),
+            is_paused: false,
+            is_step_requested: false,
+        }
+    }
+
+    pub fn add(&mut self, action: Box<dyn Action>) -> ActionHandle {
+        let handle = ActionHandle(self.next_handle);
+        self.next_handle += 1;
+        self.add_with_handle(handle, action);
+        handle
+    }
+
+    fn add_with_handle(&mut self, handle: ActionHandle, mut action: Box<dyn Action>) {
+        action.begin();
+        self.entries.push(Entry { handle, action });
+    }
+
+    pub fn add_tag(&mut self, handle: ActionHandle, tag: String) {
+        if self.is_finished(handle) {
+            return;
+        }
+        self.tags.entry(handle).or_default().insert(tag);
+    }
+
+    pub fn on_finish(&mut self, handle: ActionHandle, f: Box<dyn FnOnce()>) {
+        if self.canceled.contains(&handle) {
+            return;
+        }
+        if self.is_finished(handle) {
+            f();
+            return;
+        }
+        self.callbacks.entry(handle).or_default().push(Callback(f));
+    }
+
+    pub fn is_finished(&self, handle: ActionHandle) -> bool {
+        !self.entries.iter().any(|entry| entry.handle == handle)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, scale: f32) {
+        assert!(scale >= 0.0);
+        self.time_scale = scale;
+    }
+
+    pub fn set_tag_time_scale(&mut self, tag: String, scale: Option<f32>) {
+        match scale {
+            Some(scale) => {
+                assert!(scale >= 0.0);
+                self.tag_time_scales.insert(tag, scale);
+            }
+            None => {
+                self.tag_time_scales.remove(&tag);
+            }
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn set_paused(&mut self, value: bool) {
+        self.is_paused = value;
+    }
+
+    pub fn step(&mut self) {
+        self.is_step_requested = true;
+    }
+
+    /// Returns the time that passes for the untagged actions this tick.
+    /// Must be called before [ActionInterpreter::tick] as it resets the step request.
+    fn world_dtime(&self, dtime: Duration) -> Duration {
+        if self.is_paused && !self.is_step_requested {
+            Duration::new(0, 0)
+        } else {
+            dtime.mul_f32(self.time_scale)
+        }
+    }
+
+    /// Returns the time that passes this tick for the actions with only this tag,
+    /// or for the untagged ones if the tag is `None`.
+    /// Must be called before [ActionInterpreter::tick] as it resets the step request.
+    pub fn tag_dtime(&self, tag: Option<&str>, dtime: Duration) -> Duration {
+        match tag.and_then(|tag| self.tag_time_scales.get(tag)) {
+            Some(&scale) => dtime.mul_f32(scale),
+            None => self.world_dtime(dtime),
+        }
+    }
+
+    /// Returns the largest override of the action's tags if there's any.
+    fn tag_time_scale(&self, handle: ActionHandle) -> Option<f32> {
+        let tags = self.tags.get(&handle)?;
+        tags.iter()
+            .filter_map(|tag| self.tag_time_scales.get(tag))
+            .copied()
+            .max_by(|a, b| a.partial_cmp(b).expect("time scale can't be NaN"))
+    }
+
+    /// Returns `None` if the action must not be updated this tick.
+    fn scaled_dtime(
+        &self,
+        handle: ActionHandle,
+        dtime: Duration,
+        is_running: bool,
+    ) -> Option<Duration> {
+        if let Some(scale) = self.tag_time_scale(handle) {
+            return Some(dtime.mul_f32(scale));
+        }
+        if is_running {
+            Some(dtime.mul_f32(self.time_scale))
+        } else {
+            None
+        }
+    }
+
+    fn handles_with_tag(&self, tag: &str) -> Vec<ActionHandle> {
+        self.tags
+            .iter()
+            .filter(|(_, tags)| tags.contains(tag))
+            .map(|(&handle, _)| handle)
+            .collect()
+    }
+
+    /// Drops the actions without ending them. Their callbacks aren't called.
+    pub fn cancel(&mut self, handle: ActionHandle) {
+        self.entries.retain(|entry| entry.handle != handle);
+        self.tags.remove(&handle);
+        self.callbacks.remove(&handle);
+        if self.canceled.len() == CANCELED_MEMORY {
+            self.canceled.pop_front();
+        }
+        self.canceled.push_back(handle);
+    }
+
+    pub fn cancel_tag(&mut self, tag: &str) {
+        for handle in self.handles_with_tag(tag) {
+            self.cancel(handle);
+        }
+    }
+
+    /// Instantly plays the actions (and everything they fork) to the end.
+    pub fn skip_to_end(&mut self, handle: ActionHandle) {
+        let mut queue = Vec::new();
+        let mut i = 0;
+        while i < self.entries.len() {
+            if self.entries[i].handle == handle {
+                queue.push(self.entries.remove(i).action);
+            } else {
+                i += 1;
+            }
+        }
+        while let Some(mut action) = queue.pop() {
+            let mut steps = 0;
+            loop {
+                while let Some(mut forked_action) = action.try_fork() {
+                    forked_action.begin();
+                    queue.push(forked_action);
+                }
+                if action.is_finished() || steps == SKIP_MAX_STEPS {
+                    break;
+                }
+                action.update(SKIP_STEP);
+                steps += 1;
+            }
+            action.end();
+        }
+        self.finish(handle);
+    }
+
+    pub fn skip_tag_to_end(&mut self, tag: &str) {
+        for handle in self.handles_with_tag(tag) {
+            self.skip_to_end(handle);
+        }
+    }
+
+    fn finish(&mut self, handle: ActionHandle) {
+        self.tags.remove(&handle);
+        if let Some(callbacks) = self.callbacks.remove(&handle) {
+            for Callback(f) in callbacks {
+                f();
+            }
+        }
+    }
+
+    pub fn tick(&mut self, dtime: Duration) {
+        let is_running = !self.is_paused || self.is_step_requested;
+        self.is_step_requested = false;
+        let dtimes: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| self.scaled_dtime(entry.handle, dtime, is_running))
+            .collect();
+        let mut forked_actions = Vec::new();
+        let mut ended_handles = Vec::new();
+        let mut is_ended = Vec::with_capacity(self.entries.len());
+        for (Entry { handle, action }, dtime) in self.entries.iter_mut().zip(dtimes) {
+            // Paused actions aren't touched at all, even the finished ones.
+            let dtime = match dtime {
+                Some(dtime) => dtime,
+                None => {
+                    is_ended.push(false);
+                    continue;
+                }
+            };
+            action.update(dtime);
+            while let Some(forked_action) = action.try_fork() {
+                forked_actions.push((*handle, forked_action));
+            }
+            if action.is_finished() {
+                action.end();
+                ended_handles.push(*handle);
+            }
+            is_ended.push(action.is_finished());
+        }
+        let mut is_ended = is_ended.into_iter();
+        self.entries
+            .retain(|_| !is_ended.next().expect("bad entries count"));
+        for (handle, action) in forked_actions {
+            self.add_with_handle(handle, action);
+        }
+        for handle in ended_handles {
+            if self.is_finished(handle) {
+                self.finish(handle);
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
+
+use mq::math::Vec2;
+
+use crate::{ParticleEmitter, Sprite, SpriteId, TileLayer};
+
+/// Defines how sprites of a layer are ordered when drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortMode {
+    /// Sprites are sorted by their z values set with [Layer::set_z].
+    Z,
+
+    /// Sprites are sorted by `pos().y + z` so the lower sprites are drawn
+    /// over the upper ones. The z value acts as a bias here.
+    ///
+    /// The order is updated every frame as sprites may be moved by actions.
+    Y,
+}
+
+#[derive(Debug)]
+struct SpriteWithZ {
+    sprite: Sprite,
+    z: f32,
+
+    /// Insertion order, keeps the sort stable for equal z values.
+    seq: u64,
+}
+
+impl SpriteWithZ {
+    fn key(&self, mode: SortMode) -> f32 {
+        match mode {
+            SortMode::Z => self.z,
+            SortMode::Y => self.sprite.pos().y + self.z,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LayerData {
+    sprites: Vec<SpriteWithZ>,
+
+    /// Drawn over the sprites.
+    emitters: Vec<ParticleEmitter>,
+    tile_layers: Vec<TileLayer>,
+
+    /// Index of every sprite in the `sprites` vector.
+    indices: HashMap<SpriteId, usize>,
+
+    sort_mode: SortMode,
+    is_pickable: bool,
+    next_seq: u64,
+
+    /// The `sprites` vector needs to be sorted before the next draw.
+    is_dirty: bool,
+}
+
+impl LayerData {
+    fn index(&self, id: SpriteId) -> usize {
+        *self.indices.get(&id).expect("can't find the sprite")
+    }
+
+    fn sort_if_needed(&mut self) {
+        if !self.is_dirty && self.sort_mode == SortMode::Z {
+            return;
+        }
+        let mode = self.sort_mode;
+        // The vector is mostly sorted already so this is close to linear.
+        self.sprites.sort_by(|a, b| {
+            let key_a = a.key(mode);
+            let key_b = b.key(mode);
+            key_a
+                .partial_cmp(&key_b)
+                .expect("z can't be NaN")
+                .then(a.seq.cmp(&b.seq))
+        });
+        self.update_indices();
+        self.is_dirty = false;
+    }
+
+    fn update_indices(&mut self) {
+        for (i, sprite) in self.sprites.iter().enumerate() {
+            self.indices.insert(sprite.sprite.id(), i);
+        }
+    }
+}
+
+/// A shared handle to an ordered set of sprites.
+///
+/// Adding, removing and changing z of a sprite are O(1),
+/// the sprites are sorted lazily once before they're drawn.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    data: Rc<RefCell<LayerData>>,
+}
+
+impl Layer {
+    pub fn new() -> Self {
+        let data = LayerData {
+            sprites: Vec::new(),
+            emitters: Vec::new(),
+            tile_layers: Vec::new(),
+            indices: HashMap::new(),
+            sort_mode: SortMode::Z,
+            is_pickable: true,
+            next_seq: 0,
+            is_dirty: false,
+        };
+        Self {
+            data: Rc::new(RefCell::new(data)),
+        }
+    }
+
+    pub fn with_sort_mode(mut self, mode: SortMode) -> Self {
+        self.set_sort_mode(mode);
+        self
+    }
+
+    pub fn set_sort_mode(&mut self, mode: SortMode) {
+        let mut data = self.data.borrow_mut();
+        data.sort_mode = mode;
+        data.is_dirty = true;
+    }
+
+    pub fn with_pickable(mut self, value: bool) -> Self {
+        self.set_pickable(value);
+        self
+    }
+
+    /// Sprites of non-pickable layers are ignored by [crate::Scene::pick].
+    pub fn set_pickable(&mut self, value: bool) {
+        self.data.borrow_mut().is_pickable = value;
+    }
+
+    /// Does nothing if the sprite is already in the layer.
+    pub fn add(&mut self, sprite: &Sprite) {
+        let mut data = self.data.borrow_mut();
+        if data.indices.contains_key(&sprite.id()) {
+            return;
+        }
+        let seq = data.next_seq;
+        data.next_seq += 1;
+        let index = data.sprites.len();
+        data.indices.insert(sprite.id(), index);
+        data.sprites.push(SpriteWithZ {
+            sprite: sprite.clone(),
+            z: 0.0,
+            seq,
+        });
+        data.is_dirty = true;
+    }
+
+    pub fn set_z(&mut self, sprite: &Sprite, z: f32) {
+        let mut data = self.data.borrow_mut();
+        let index = data.index(sprite.id());
+        data.sprites[index].z = z;
+        data.is_dirty = true;
+    }
+
+    pub fn z(&self, sprite: &Sprite) -> f32 {
+        let data = self.data.borrow();
+        data.sprites[data.index(sprite.id())].z
+    }
+
+    pub fn remove(&mut self, sprite: &Sprite) {
+        self.remove_by_id(sprite.id());
+    }
+
+    pub fn remove_by_id(&mut self, id: SpriteId) {
+        let mut data = self.data.borrow_mut();
+        let index = match data.indices.remove(&id) {
+            Some(index) => index,
+            None => return,
+        };
+        data.sprites.swap_remove(index);
+        if let Some(moved) = data.sprites.get(index) {
+            let moved_id = moved.sprite.id();
+            data.indices.insert(moved_id, index);
+        }
+        data.is_dirty = true;
+    }
+
+    pub fn has_sprite(&self, sprite: &Sprite) -> bool {
+        self.data.borrow().indices.contains_key(&sprite.id())
+    }
+
+    /// Returns the sprite by its id if it belongs to this layer.
+    pub fn sprite(&self, id: SpriteId) -> Option<Sprite> {
+        let data = self.data.borrow();
+        let index = *data.indices.get(&id)?;
+        Some(data.sprites[index].sprite.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.borrow().sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn add_emitter(&mut self, emitter: &ParticleEmitter) {
+        assert!(!self.has_emitter(emitter));
+        self.data.borrow_mut().emitters.push(emitter.clone());
+    }
+
+    pub fn remove_emitter(&mut self, emitter: &ParticleEmitter) {
+        let emitters = &mut self.data.borrow_mut().emitters;
+        emitters.retain(|other| !other.is_same(emitter));
+    }
+
+    pub fn has_emitter(&self, emitter: &ParticleEmitter) -> bool {
+        let emitters = &self.data.borrow().emitters;
+        emitters.iter().any(|other| other.is_same(emitter))
+    }
+
+    /// Tile layers are drawn under the sprites in the order they were added.
+    pub fn add_tile_layer(&mut self, tile_layer: &TileLayer) {
+        assert!(!self.has_tile_layer(tile_layer));
+        self.data.borrow_mut().tile_layers.push(tile_layer.clone());
+    }
+
+    pub fn remove_tile_layer(&mut self, tile_layer: &TileLayer) {
+        let tile_layers = &mut self.data.borrow_mut().tile_layers;
+        tile_layers.retain(|other| !other.is_same(tile_layer));
+    }
+
+    pub fn has_tile_layer(&self, tile_layer: &TileLayer) -> bool {
+        let tile_layers = &self.data.borrow().tile_layers;
+        tile_layers.iter().any(|other| other.is_same(tile_layer))
+    }
+
+    /// `dtime` returns the time passed for the emitter's tag.
+    pub(crate) fn update_emitters(&mut self, dtime: impl Fn(Option<&str>) -> Duration) {
+        for emitter in &mut self.data.borrow_mut().emitters {
+            let dtime = dtime(emitter.tag().as_deref());
+            emitter.update(dtime);
+        }
+    }
+
+    /// Calls `f` for every sprite in the drawing order.
+    pub(crate) fn for_each_sorted(&self, mut f: impl FnMut(&Sprite)) {
+        let mut data = self.data.borrow_mut();
+        data.sort_if_needed();
+        for z_sprite in &data.sprites {
+            f(&z_sprite.sprite);
+        }
+    }
+
+    /// Returns the top-most visible sprite that contains the point.
+    pub(crate) fn pick(&self, point: Vec2) -> Option<Sprite> {
+        let mut data = self.data.borrow_mut();
+        if !data.is_pickable {
+            return None;
+        }
+        data.sort_if_needed();
+        data.sprites
+            .iter()
+            .rev()
+            .map(|z_sprite| &z_sprite.sprite)
+            .find(|sprite| sprite.is_visible() && sprite.contains(point))
+            .cloned()
+    }
+
+    pub(crate) fn draw(&self) {
+        for tile_layer in &self.data.borrow().tile_layers {
+            tile_layer.draw();
+        }
+        self.for_each_sorted(|sprite| sprite.draw());
+        for emitter in &self.data.borrow().emitters {
+            emitter.draw();
+        }
+    }
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,13 +1,27 @@
-use std::{cell::RefCell, fmt, rc::Rc, time::Duration};
+use std::{fmt, time::Duration};
+
+use mq::math::Vec2;
+
+use crate::interpreter::ActionInterpreter;
 
 pub use crate::{
     action::{Action, Boxed},
-    sprite::{Facing, Sprite},
+    easing::Easing,
+    interpreter::ActionHandle,
+    layer::{Layer, SortMode},
+    particle::{EmitterParams, ParticleEmitter},
+    sprite::{Facing, PickMode, Sprite, SpriteId},
+    tile_layer::{Atlas, AutotileRule, Grid, TileLayer},
 };
 
 pub mod action;
 
+mod easing;
+mod interpreter;
+mod layer;
+mod particle;
 mod sprite;
+mod tile_layer;
 
 pub type Result<T = ()> = std::result::Result<T, Error>;
 
@@ -32,141 +46,151 @@ impl std::error::Error for Error {
     }
 }
 
-#[derive(Debug)]
-struct SpriteWithZ {
-    sprite: Sprite,
-    z: f32,
+/// Reported by [Scene::move_mouse] when the hovered sprite changes.
+#[derive(Debug, Clone)]
+pub enum HoverEvent {
+    Enter(Sprite),
+    Leave(Sprite),
 }
 
 #[derive(Debug)]
-struct LayerData {
-    sprites: Vec<SpriteWithZ>,
-}
-
-#[derive(Debug, Clone)]
-pub struct Layer {
-    data: Rc<RefCell<LayerData>>,
+pub struct Scene {
+    layers: Vec<Layer>,
+    interpreter: ActionInterpreter,
+    hovered: Option<Sprite>,
 }
 
-impl Layer {
-    pub fn new() -> Self {
-        let data = LayerData {
-            sprites: Vec::new(),
-        };
+impl Scene {
+    pub fn new(layers: Vec<Layer>) -> Self {
         Self {
-            data: Rc::new(RefCell::new(data)),
+            layers,
+            interpreter: ActionInterpreter::new(),
+            hovered: None,
         }
     }
 
-    pub fn add(&mut self, sprite: &Sprite) {
-        let sprite = SpriteWithZ {
-            sprite: sprite.clone(),
-            z: 0.0,
+    /// Returns the top-most visible sprite under the point.
+    ///
+    /// Layers are checked from the last one to the first one,
+    /// sprites of a layer in the reversed drawing order.
+    pub fn pick(&self, point: Vec2) -> Option<Sprite> {
+        self.layers.iter().rev().find_map(|layer| layer.pick(point))
+    }
+
+    /// Updates the hovered sprite, returns the leave and enter events if it has changed.
+    pub fn move_mouse(&mut self, point: Vec2) -> Vec<HoverEvent> {
+        let picked = self.pick(point);
+        let is_same = match (&self.hovered, &picked) {
+            (Some(old), Some(new)) => old.is_same(new),
+            (None, None) => true,
+            _ => false,
         };
-        self.data.borrow_mut().sprites.push(sprite);
-        self.sort();
-    }
-
-    pub fn set_z(&mut self, sprite: &Sprite, z: f32) {
-        {
-            let sprites = &mut self.data.borrow_mut().sprites;
-            let sprite = sprites
-                .iter_mut()
-                .find(|other| other.sprite.is_same(sprite))
-                .expect("can't find the sprite");
-            sprite.z = z;
+        if is_same {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        if let Some(old) = self.hovered.take() {
+            events.push(HoverEvent::Leave(old));
+        }
+        if let Some(new) = &picked {
+            events.push(HoverEvent::Enter(new.clone()));
         }
-        self.sort();
+        self.hovered = picked;
+        events
     }
 
-    fn sort(&mut self) {
-        let sprites = &mut self.data.borrow_mut().sprites;
-        sprites.sort_by(|a, b| a.z.partial_cmp(&b.z).expect("can't find the sprite"));
+    pub fn hovered(&self) -> Option<&Sprite> {
+        self.hovered.as_ref()
     }
 
-    pub fn remove(&mut self, sprite: &Sprite) {
-        let mut data = self.data.borrow_mut();
-        data.sprites.retain(|other| !sprite.is_same(&other.sprite))
+    pub fn draw(&self) {
+        for layer in &self.layers {
+            layer.draw();
+        }
     }
 
-    pub fn has_sprite(&self, sprite: &Sprite) -> bool {
-        let sprites = &self.data.borrow_mut().sprites;
-        sprites.iter().any(|other| other.sprite.is_same(sprite))
+    pub fn add_action(&mut self, action: Box<dyn Action>) -> ActionHandle {
+        self.interpreter.add(action)
     }
-}
 
-impl Default for Layer {
-    fn default() -> Self {
-        Self::new()
+    /// Tags the action (e.g. with "unit:5") so it can be
+    /// found by [Scene::cancel_tag] or [Scene::skip_tag_to_end].
+    pub fn add_tag(&mut self, handle: ActionHandle, tag: impl Into<String>) {
+        self.interpreter.add_tag(handle, tag.into());
     }
-}
 
-#[derive(Debug)]
-pub struct Scene {
-    layers: Vec<Layer>,
-    interpreter: ActionInterpreter,
-}
+    /// Calls `f` once the action and all its forks are finished
+    /// or skipped. Isn't called for the canceled actions, unless
+    /// many other actions were canceled since then.
+    pub fn on_finish(&mut self, handle: ActionHandle, f: Box<dyn FnOnce()>) {
+        self.interpreter.on_finish(handle, f);
+    }
 
-impl Scene {
-    pub fn new(layers: Vec<Layer>) -> Self {
-        Self {
-            layers,
-            interpreter: ActionInterpreter::new(),
-        }
+    /// Checks if the action and all its forks are finished or canceled.
+    pub fn is_finished(&self, handle: ActionHandle) -> bool {
+        self.interpreter.is_finished(handle)
     }
 
-    pub fn draw(&self) {
-        for layer in &self.layers {
-            for z_sprite in &layer.data.borrow().sprites {
-                z_sprite.sprite.draw();
-            }
-        }
+    /// Stops the action and all its forks where they are.
+    pub fn cancel(&mut self, handle: ActionHandle) {
+        self.interpreter.cancel(handle);
     }
 
-    pub fn add_action(&mut self, action: Box<dyn Action>) {
-        self.interpreter.add(action);
+    pub fn cancel_tag(&mut self, tag: &str) {
+        self.interpreter.cancel_tag(tag);
     }
 
-    pub fn tick(&mut self, dtime: Duration) {
-        self.interpreter.tick(dtime);
+    /// Instantly plays the action and all its forks to the end.
+    /// The actions that never finish are ended after a number of big steps.
+    pub fn skip_to_end(&mut self, handle: ActionHandle) {
+        self.interpreter.skip_to_end(handle);
     }
 
-    pub fn any_unfinished_actions(&self) -> bool {
-        !self.interpreter.actions.is_empty()
+    pub fn skip_tag_to_end(&mut self, tag: &str) {
+        self.interpreter.skip_tag_to_end(tag);
     }
-}
 
-#[derive(Debug)]
-struct ActionInterpreter {
-    actions: Vec<Box<dyn Action>>,
-}
+    pub fn time_scale(&self) -> f32 {
+        self.interpreter.time_scale()
+    }
 
-impl ActionInterpreter {
-    pub fn new() -> Self {
-        Self {
-            actions: Vec::new(),
-        }
+    /// Multiplies the time passed to [Scene::tick], e.g. `2.0` plays actions twice as fast.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.interpreter.set_time_scale(scale);
+    }
+
+    /// Overrides the time scale for the actions with the tag, `None` removes the override.
+    ///
+    /// Overridden actions and particle emitters with the tag ignore both
+    /// the global time scale and the pause, which is handy for the UI animations. If several tags of an action
+    /// are overridden, the largest scale is used.
+    pub fn set_tag_time_scale(&mut self, tag: impl Into<String>, scale: Option<f32>) {
+        self.interpreter.set_tag_time_scale(tag.into(), scale);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.interpreter.is_paused()
     }
 
-    pub fn add(&mut self, mut action: Box<dyn Action>) {
-        action.begin();
-        self.actions.push(action);
+    /// Freezes all actions except for the ones with overridden tag time scales.
+    pub fn set_paused(&mut self, value: bool) {
+        self.interpreter.set_paused(value);
+    }
+
+    /// Makes the next [Scene::tick] update the paused actions once, for debugging.
+    pub fn step(&mut self) {
+        self.interpreter.step();
     }
 
     pub fn tick(&mut self, dtime: Duration) {
-        let mut forked_actions = Vec::new();
-        for action in &mut self.actions {
-            action.update(dtime);
-            while let Some(forked_action) = action.try_fork() {
-                forked_actions.push(forked_action);
-            }
-            if action.is_finished() {
-                action.end();
-            }
+        let interpreter = &self.interpreter;
+        for layer in &mut self.layers {
+            layer.update_emitters(|tag| interpreter.tag_dtime(tag, dtime));
         }
-        for action in forked_actions {
-            self.add(action);
-        }
-        self.actions.retain(|action| !action.is_finished());
+        self.interpreter.tick(dtime);
+    }
+
+    pub fn any_unfinished_actions(&self) -> bool {
+        !self.interpreter.is_empty()
     }
 }
@@ -0,0 +1,258 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use mq::{
+    color::Color,
+    math::Vec2,
+    rand::gen_range,
+    texture::{self, DrawTextureParams, Texture2D},
+};
+
+use crate::easing::interpolate;
+
+#[derive(Debug, Clone)]
+pub struct EmitterParams {
+    pub texture: Texture2D,
+
+    /// Particles per second while the emitter is emitting.
+    pub rate: f32,
+
+    pub lifetime: Duration,
+
+    /// Random lifetime deviation, as a fraction of `lifetime`.
+    pub lifetime_spread: f32,
+
+    /// Particle's height at scale `1.0`.
+    pub size: f32,
+
+    /// Particles are spawned randomly inside of this radius around the emitter.
+    pub spawn_radius: f32,
+
+    pub velocity: Vec2,
+
+    /// Random deviation of the initial velocity, per axis.
+    pub velocity_spread: Vec2,
+
+    /// Constant acceleration.
+    pub gravity: Vec2,
+
+    pub color_start: Color,
+    pub color_end: Color,
+    pub scale_start: f32,
+    pub scale_end: f32,
+}
+
+impl EmitterParams {
+    pub fn new(texture: Texture2D, size: f32) -> Self {
+        Self {
+            texture,
+            rate: 10.0,
+            lifetime: Duration::from_secs(1),
+            lifetime_spread: 0.0,
+            size,
+            spawn_radius: 0.0,
+            velocity: Vec2::new(0.0, 0.0),
+            velocity_spread: Vec2::new(0.0, 0.0),
+            gravity: Vec2::new(0.0, 0.0),
+            color_start: Color::new(1.0, 1.0, 1.0, 1.0),
+            color_end: Color::new(1.0, 1.0, 1.0, 0.0),
+            scale_start: 1.0,
+            scale_end: 1.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Particle {
+    pos: Vec2,
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+}
+
+impl Particle {
+    /// Life progress in the `0.0..1.0` range.
+    fn k(&self) -> f32 {
+        (self.age / self.lifetime).min(1.0)
+    }
+}
+
+#[derive(Debug)]
+struct EmitterData {
+    params: EmitterParams,
+    pos: Vec2,
+    is_emitting: bool,
+
+    /// The running [crate::action::Emit] actions, each keeps the emitter emitting.
+    emissions: usize,
+
+    /// Fractional particles left from the previous updates.
+    spawn_debt: f32,
+
+    pending_burst: usize,
+    particles: Vec<Particle>,
+
+    /// Particles move with the time scale of this tag's actions.
+    tag: Option<String>,
+}
+
+impl EmitterData {
+    fn is_emitting(&self) -> bool {
+        self.is_emitting || self.emissions > 0
+    }
+
+    fn spawn(&mut self) {
+        let p = &self.params;
+        let angle = gen_range(0.0, std::f32::consts::PI * 2.0);
+        let radius = gen_range(0.0, p.spawn_radius);
+        let spread = |max: f32| gen_range(-max, max);
+        let lifetime_k = 1.0 + spread(p.lifetime_spread);
+        let particle = Particle {
+            pos: self.pos + Vec2::new(angle.cos(), angle.sin()) * radius,
+            velocity: p.velocity
+                + Vec2::new(spread(p.velocity_spread.x), spread(p.velocity_spread.y)),
+            age: 0.0,
+            lifetime: (p.lifetime.as_secs_f32() * lifetime_k).max(f32::EPSILON),
+        };
+        self.particles.push(particle);
+    }
+}
+
+/// A shared handle to a particle system.
+///
+/// Add it to a [crate::Layer] to draw it, particles are updated by [crate::Scene::tick]
+/// with the same time scale and pause as the untagged actions or the emitter's tag.
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    data: Rc<RefCell<EmitterData>>,
+}
+
+impl ParticleEmitter {
+    pub fn new(params: EmitterParams) -> Self {
+        let data = EmitterData {
+            params,
+            pos: Vec2::new(0.0, 0.0),
+            is_emitting: false,
+            emissions: 0,
+            spawn_debt: 0.0,
+            pending_burst: 0,
+            particles: Vec::new(),
+            tag: None,
+        };
+        Self {
+            data: Rc::new(RefCell::new(data)),
+        }
+    }
+
+    /// Makes the particles follow [crate::Scene::set_tag_time_scale] overrides of the tag.
+    pub fn with_tag(self, tag: impl Into<String>) -> Self {
+        self.data.borrow_mut().tag = Some(tag.into());
+        self
+    }
+
+    pub fn tag(&self) -> Option<String> {
+        self.data.borrow().tag.clone()
+    }
+
+    pub fn pos(&self) -> Vec2 {
+        self.data.borrow().pos
+    }
+
+    /// Moves the emitter, the already spawned particles stay where they are.
+    pub fn set_pos(&mut self, pos: Vec2) {
+        self.data.borrow_mut().pos = pos;
+    }
+
+    pub fn is_emitting(&self) -> bool {
+        self.data.borrow().is_emitting()
+    }
+
+    /// Starts or stops the continuous emission with the `rate` from the params.
+    ///
+    /// Doesn't stop the emissions of the running [crate::action::Emit] actions.
+    pub fn set_emitting(&mut self, value: bool) {
+        let mut data = self.data.borrow_mut();
+        data.is_emitting = value;
+        data.spawn_debt = 0.0;
+    }
+
+    /// Keeps the emitter emitting until the matching [ParticleEmitter::end_emission].
+    pub(crate) fn begin_emission(&mut self) {
+        let mut data = self.data.borrow_mut();
+        if !data.is_emitting() {
+            data.spawn_debt = 0.0;
+        }
+        data.emissions += 1;
+    }
+
+    pub(crate) fn end_emission(&mut self) {
+        let mut data = self.data.borrow_mut();
+        assert!(data.emissions > 0, "no emission to end");
+        data.emissions -= 1;
+    }
+
+    /// Spawns `count` particles on the next update.
+    pub fn burst(&mut self, count: usize) {
+        self.data.borrow_mut().pending_burst += count;
+    }
+
+    pub fn particles_count(&self) -> usize {
+        self.data.borrow().particles.len()
+    }
+
+    /// Checks if the emitter has nothing to emit or draw anymore.
+    pub fn is_finished(&self) -> bool {
+        let data = self.data.borrow();
+        !data.is_emitting() && data.pending_burst == 0 && data.particles.is_empty()
+    }
+
+    pub fn is_same(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+
+    pub(crate) fn update(&mut self, dtime: Duration) {
+        let mut data = self.data.borrow_mut();
+        let dtime = dtime.as_secs_f32();
+        let gravity = data.params.gravity;
+        for particle in &mut data.particles {
+            particle.age += dtime;
+            particle.velocity += gravity * dtime;
+            particle.pos += particle.velocity * dtime;
+        }
+        data.particles
+            .retain(|particle| particle.age < particle.lifetime);
+        for _ in 0..data.pending_burst {
+            data.spawn();
+        }
+        data.pending_burst = 0;
+        if data.is_emitting() {
+            data.spawn_debt += data.params.rate * dtime;
+            while data.spawn_debt >= 1.0 {
+                data.spawn();
+                data.spawn_debt -= 1.0;
+            }
+        }
+    }
+
+    pub(crate) fn draw(&self) {
+        let data = self.data.borrow();
+        let p = &data.params;
+        let texture = p.texture;
+        let aspect = texture.width() / texture.height();
+        for particle in &data.particles {
+            let k = particle.k();
+            let scale = p.scale_start + (p.scale_end - p.scale_start) * k;
+            let size = Vec2::new(p.size * aspect, p.size) * scale;
+            let pos = particle.pos - size * 0.5;
+            texture::draw_texture_ex(
+                texture,
+                pos.x,
+                pos.y,
+                interpolate(p.color_start, p.color_end, k),
+                DrawTextureParams {
+                    dest_size: Some(size),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
@@ -1,18 +1,49 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use mq::{
     color::Color,
     math::{Rect, Vec2},
     text::{self, Font},
-    texture::{self, DrawTextureParams, Texture2D},
+    texture::{self, DrawTextureParams, Image, Texture2D},
 };
 
+/// Alpha values below this are considered transparent by [PickMode::Alpha].
+const PICK_ALPHA_THRESHOLD: f32 = 0.1;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Facing {
     Left,
     Right,
 }
 
+/// A stable handle of a sprite, shared by all of its shallow clones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpriteId(u64);
+
+impl SpriteId {
+    fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        SpriteId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Defines how [Sprite::contains] (and so [crate::Scene::pick]) tests points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PickMode {
+    /// Any point inside of the sprite's rect hits it.
+    Rect,
+
+    /// Only non-transparent pixels of a texture hit it.
+    /// Texture data is read back from the GPU when a sprite's frame
+    /// is tested for the first time, so use it sparingly.
+    Alpha,
+}
+
 #[derive(Clone, Debug)]
 enum Drawable {
     Texture(Texture2D),
@@ -45,6 +76,7 @@ impl Drawable {
 
 #[derive(Debug)]
 struct SpriteData {
+    id: SpriteId,
     drawable: Option<Drawable>,
     drawables: HashMap<String, Option<Drawable>>,
     current_frame_name: String,
@@ -55,6 +87,10 @@ struct SpriteData {
     color: Color,
     offset: Vec2,
     facing: Facing,
+    pick_mode: PickMode,
+
+    /// Lazily read texture data of the frames, see [PickMode::Alpha].
+    alpha_masks: HashMap<String, Image>,
 }
 
 #[derive(Debug, Clone)]
@@ -66,6 +102,7 @@ impl Sprite {
     pub fn deep_clone(&self) -> Self {
         let data = self.data.borrow();
         let cloned_data = SpriteData {
+            id: SpriteId::new(),
             drawable: data.drawable.clone(),
             drawables: data.drawables.clone(),
             current_frame_name: data.current_frame_name.clone(),
@@ -76,6 +113,8 @@ impl Sprite {
             color: data.color,
             offset: data.offset,
             facing: data.facing,
+            pick_mode: data.pick_mode,
+            alpha_masks: data.alpha_masks.clone(),
         };
         Sprite {
             data: Rc::new(RefCell::new(cloned_data)),
@@ -88,6 +127,7 @@ impl Sprite {
         let mut drawables = HashMap::new();
         drawables.insert("".into(), None);
         let data = SpriteData {
+            id: SpriteId::new(),
             drawable: Some(drawable),
             drawables,
             current_frame_name: "".into(),
@@ -98,6 +138,8 @@ impl Sprite {
             color: Color::new(1.0, 1.0, 1.0, 1.0),
             pos: Vec2::new(0.0, 0.0),
             facing: Facing::Right,
+            pick_mode: PickMode::Rect,
+            alpha_masks: HashMap::new(),
         };
         let data = Rc::new(RefCell::new(data));
         Self { data }
@@ -233,23 +275,62 @@ impl Sprite {
         }
     }
 
+    pub fn id(&self) -> SpriteId {
+        self.data.borrow().id
+    }
+
     pub fn pos(&self) -> Vec2 {
         let data = self.data.borrow();
         data.pos - data.offset
     }
 
+    /// Returns the area the sprite is drawn at.
     pub fn rect(&self) -> Rect {
-        // TODO: `self.dimensions` + `graphics::transform_rect(param)` ?
-        let pos = self.pos();
-        let data = self.data.borrow();
-        let r = data.dimensions;
         // TODO: angle?
+        let data = self.data.borrow();
+        let w = data.dimensions.w * data.scale.x;
+        let h = data.dimensions.h * data.scale.y;
+        // A mirrored sprite is drawn to the left of its position.
+        let x = if w < 0.0 { data.pos.x + w } else { data.pos.x };
         Rect {
-            x: pos.x,
-            y: pos.y,
-            w: r.w * data.scale.x,
-            h: r.h * data.scale.y,
+            x,
+            y: data.pos.y,
+            w: w.abs(),
+            h,
+        }
+    }
+
+    pub fn set_pick_mode(&mut self, mode: PickMode) {
+        self.data.borrow_mut().pick_mode = mode;
+    }
+
+    /// Checks if the point hits the sprite according to its [PickMode].
+    pub fn contains(&self, point: Vec2) -> bool {
+        if !self.rect().contains(point) {
+            return false;
         }
+        let mut data = self.data.borrow_mut();
+        let data = &mut *data;
+        let texture = match data.drawable {
+            Some(Drawable::Texture(texture)) if data.pick_mode == PickMode::Alpha => texture,
+            _ => return true,
+        };
+        // Works for the mirrored sprites too as both values are negative then.
+        let size = Vec2::new(data.dimensions.w, data.dimensions.h) * data.scale;
+        let k = (point - data.pos) / size;
+        let mask = data
+            .alpha_masks
+            .entry(data.current_frame_name.clone())
+            .or_insert_with(|| texture.get_texture_data());
+        let x = (k.x * mask.width() as f32) as u32;
+        let y = (k.y * mask.height() as f32) as u32;
+        let x = x.min(mask.width() as u32 - 1);
+        let y = y.min(mask.height() as u32 - 1);
+        mask.get_pixel(x, y).a > PICK_ALPHA_THRESHOLD
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.data.borrow().color.a > 0.0
     }
 
     pub fn color(&self) -> Color {
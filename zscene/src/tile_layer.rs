@@ -0,0 +1,449 @@
+use std::{cell::RefCell, collections::HashMap, fmt, hash::Hash, rc::Rc};
+
+use mq::{
+    color::Color,
+    math::{Rect, Vec2, Vec3},
+    models::{self, Mesh, Vertex},
+    texture::Texture2D,
+    window,
+};
+
+/// `geometry()` of macroquad clamps everything above 5000 indices.
+const MAX_QUADS_PER_MESH: usize = 800;
+
+/// A texture with equally sized tiles, indexed row by row.
+#[derive(Debug, Clone, Copy)]
+pub struct Atlas {
+    pub texture: Texture2D,
+    pub columns: u16,
+    pub rows: u16,
+}
+
+impl Atlas {
+    pub fn new(texture: Texture2D, columns: u16, rows: u16) -> Self {
+        assert!(columns > 0 && rows > 0);
+        Self {
+            texture,
+            columns,
+            rows,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        usize::from(self.columns) * usize::from(self.rows)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Tile's texture coordinates, inset by half a texel to avoid bleeding.
+    fn uv_rect(&self, index: u16) -> Rect {
+        assert!(usize::from(index) < self.len(), "Bad tile index: {}", index);
+        let w = 1.0 / f32::from(self.columns);
+        let h = 1.0 / f32::from(self.rows);
+        let half_texel_x = 0.5 / self.texture.width();
+        let half_texel_y = 0.5 / self.texture.height();
+        let x = f32::from(index % self.columns) * w;
+        let y = f32::from(index / self.columns) * h;
+        Rect::new(
+            x + half_texel_x,
+            y + half_texel_y,
+            w - half_texel_x * 2.0,
+            h - half_texel_y * 2.0,
+        )
+    }
+}
+
+/// How [TileLayer::autotile] picks the tile for a terrain kind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AutotileRule {
+    /// Always the same tile.
+    Fixed(u16),
+
+    /// 16 consecutive tiles starting from `first`, one for every
+    /// combination of the sides that border a different terrain
+    /// (north = 1, east = 2, south = 4, west = 8), e.g. water shores.
+    ///
+    /// Sides on the edge of the layer don't count as borders.
+    Edges { first: u16 },
+}
+
+/// How the tiles of a [TileLayer] are placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Grid {
+    /// Axis-aligned rectangles, `pos` is the top-left corner of the `(0, 0)` tile.
+    Square,
+
+    /// Diamonds with the x axis going right-down and the y axis going left-down.
+    /// `pos` is the top corner of the `(0, 0)` tile, the tile size is
+    /// the diamond's width and height.
+    Isometric,
+}
+
+struct Cache {
+    view: Rect,
+
+    /// The layer's color the vertices are tinted with.
+    color: Color,
+
+    meshes: Vec<Mesh>,
+}
+
+impl fmt::Debug for Cache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Cache")
+            .field("view", &self.view)
+            .field("color", &self.color)
+            .field("meshes", &self.meshes.len())
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+struct TileLayerData {
+    atlas: Atlas,
+    grid: Grid,
+    width: i32,
+    height: i32,
+    tile_size: Vec2,
+    pos: Vec2,
+    color: Color,
+    tiles: Vec<Option<u16>>,
+
+    /// How high every tile is raised.
+    elevations: Vec<f32>,
+
+    /// Meshes of the visible tiles, rebuilt on changes and camera moves.
+    /// Color changes only retint them.
+    cache: Option<Cache>,
+}
+
+impl TileLayerData {
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some((y * self.width + x) as usize)
+    }
+
+    /// The world rect covered by the current camera.
+    fn view() -> Rect {
+        // Only reads the current camera's matrix.
+        let projection = unsafe { window::get_internal_gl().quad_gl.get_projection_matrix() };
+        let inverse = projection.inverse();
+        let a = inverse.transform_point3(Vec3::new(-1.0, -1.0, 0.0));
+        let b = inverse.transform_point3(Vec3::new(1.0, 1.0, 0.0));
+        let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+        Rect::new(x, y, a.x.max(b.x) - x, a.y.max(b.y) - y)
+    }
+
+    /// Tiles to draw, in the drawing order.
+    fn visible_tiles(&self, view: Rect) -> Vec<(i32, i32)> {
+        match self.grid {
+            Grid::Square => {
+                let (pos, size) = (self.pos, self.tile_size);
+                let max_elevation = self.elevations.iter().cloned().fold(0.0, f32::max);
+                let to_tile = |world: f32, origin: f32, size: f32| (world - origin) / size;
+                let x0 = to_tile(view.x, pos.x, size.x).floor() as i32;
+                let x1 = to_tile(view.x + view.w, pos.x, size.x).ceil() as i32;
+                let y0 = to_tile(view.y, pos.y, size.y).floor() as i32;
+                let y1 = to_tile(view.y + view.h + max_elevation, pos.y, size.y).ceil() as i32;
+                let xs = x0.max(0)..x1.min(self.width);
+                (y0.max(0)..y1.min(self.height))
+                    .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                    .collect()
+            }
+            Grid::Isometric => {
+                // Back to front, so the raised tiles cover the ones behind them.
+                let mut tiles: Vec<_> = (0..self.height)
+                    .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+                    .filter(|&(x, y)| self.bounds(x, y).overlaps(&view))
+                    .collect();
+                tiles.sort_by_key(|&(x, y)| x + y);
+                tiles
+            }
+        }
+    }
+
+    /// Corners of the tile in the clockwise order, starting from the one
+    /// that gets the top-left corner of the atlas tile.
+    fn corners(&self, x: i32, y: i32) -> [Vec2; 4] {
+        let size = self.tile_size;
+        let index = self.index(x, y).expect("Bad tile index");
+        let lift = Vec2::new(0.0, -self.elevations[index]);
+        let (x, y) = (x as f32, y as f32);
+        match self.grid {
+            Grid::Square => {
+                let p = self.pos + lift + Vec2::new(x * size.x, y * size.y);
+                [
+                    p,
+                    p + Vec2::new(size.x, 0.0),
+                    p + size,
+                    p + Vec2::new(0.0, size.y),
+                ]
+            }
+            Grid::Isometric => {
+                let half = size * 0.5;
+                let top = self.pos + lift + Vec2::new((x - y) * half.x, (x + y) * half.y);
+                [
+                    top,
+                    top + half,
+                    top + Vec2::new(0.0, size.y),
+                    top + Vec2::new(-half.x, half.y),
+                ]
+            }
+        }
+    }
+
+    fn bounds(&self, x: i32, y: i32) -> Rect {
+        let corners = self.corners(x, y);
+        let (min_x, max_x) = (corners[3].x.min(corners[0].x), corners[1].x);
+        let (min_y, max_y) = (corners[0].y, corners[2].y);
+        Rect::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    fn build_meshes(&self, view: Rect) -> Vec<Mesh> {
+        let mut meshes = Vec::new();
+        let mut mesh = new_mesh(self.atlas.texture);
+        for (x, y) in self.visible_tiles(view) {
+            let index = self.index(x, y).expect("Bad visible tile");
+            let tile = match self.tiles[index] {
+                Some(tile) => tile,
+                None => continue,
+            };
+            if mesh.indices.len() / 6 == MAX_QUADS_PER_MESH {
+                meshes.push(std::mem::replace(&mut mesh, new_mesh(self.atlas.texture)));
+            }
+            let uv = self.atlas.uv_rect(tile);
+            let uvs = [
+                Vec2::new(uv.x, uv.y),
+                Vec2::new(uv.x + uv.w, uv.y),
+                Vec2::new(uv.x + uv.w, uv.y + uv.h),
+                Vec2::new(uv.x, uv.y + uv.h),
+            ];
+            let first = mesh.vertices.len() as u16;
+            for (corner, &uv) in self.corners(x, y).iter().zip(&uvs) {
+                mesh.vertices.push(Vertex {
+                    position: Vec3::new(corner.x, corner.y, 0.0),
+                    uv,
+                    color: self.color,
+                });
+            }
+            for &i in &[0, 1, 2, 0, 2, 3] {
+                mesh.indices.push(first + i);
+            }
+        }
+        if !mesh.indices.is_empty() {
+            meshes.push(mesh);
+        }
+        meshes
+    }
+}
+
+fn new_mesh(texture: Texture2D) -> Mesh {
+    Mesh {
+        vertices: Vec::with_capacity(MAX_QUADS_PER_MESH * 4),
+        indices: Vec::with_capacity(MAX_QUADS_PER_MESH * 6),
+        texture: Some(texture),
+    }
+}
+
+/// A shared handle to a grid of atlas tiles drawn in one batched pass.
+///
+/// Add it to a [crate::Layer] to draw it under the layer's sprites.
+/// Only the tiles inside of the camera's view are drawn.
+///
+/// The atlas tiles are drawn as is on the [Grid::Square] grids and
+/// turned into diamonds on the [Grid::Isometric] ones.
+#[derive(Debug, Clone)]
+pub struct TileLayer {
+    data: Rc<RefCell<TileLayerData>>,
+}
+
+impl TileLayer {
+    /// Creates an empty `width` by `height` grid with the top-left corner at zero.
+    pub fn new(atlas: Atlas, width: i32, height: i32, tile_size: Vec2) -> Self {
+        assert!(width >= 0 && height >= 0);
+        let count = (width * height) as usize;
+        let data = TileLayerData {
+            atlas,
+            grid: Grid::Square,
+            width,
+            height,
+            tile_size,
+            pos: Vec2::new(0.0, 0.0),
+            color: Color::new(1.0, 1.0, 1.0, 1.0),
+            tiles: vec![None; count],
+            elevations: vec![0.0; count],
+            cache: None,
+        };
+        Self {
+            data: Rc::new(RefCell::new(data)),
+        }
+    }
+
+    pub fn with_grid(mut self, grid: Grid) -> Self {
+        self.set_grid(grid);
+        self
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.data.borrow().grid
+    }
+
+    pub fn set_grid(&mut self, grid: Grid) {
+        let mut data = self.data.borrow_mut();
+        data.grid = grid;
+        data.cache = None;
+    }
+
+    pub fn width(&self) -> i32 {
+        self.data.borrow().width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.data.borrow().height
+    }
+
+    pub fn tile_size(&self) -> Vec2 {
+        self.data.borrow().tile_size
+    }
+
+    pub fn pos(&self) -> Vec2 {
+        self.data.borrow().pos
+    }
+
+    /// Moves the grid, see [Grid] for where its origin is.
+    pub fn set_pos(&mut self, pos: Vec2) {
+        let mut data = self.data.borrow_mut();
+        data.pos = pos;
+        data.cache = None;
+    }
+
+    pub fn color(&self) -> Color {
+        self.data.borrow().color
+    }
+
+    /// Cheap enough to animate the color every frame.
+    pub fn set_color(&mut self, color: Color) {
+        self.data.borrow_mut().color = color;
+    }
+
+    /// Returns `None` for the empty tiles and for the tiles outside of the grid.
+    pub fn tile(&self, x: i32, y: i32) -> Option<u16> {
+        let data = self.data.borrow();
+        data.index(x, y).and_then(|index| data.tiles[index])
+    }
+
+    pub fn set_tile(&mut self, x: i32, y: i32, tile: Option<u16>) {
+        let mut data = self.data.borrow_mut();
+        let index = data.index(x, y).expect("Tile is outside of the layer");
+        data.tiles[index] = tile;
+        data.cache = None;
+    }
+
+    pub fn elevation(&self, x: i32, y: i32) -> f32 {
+        let data = self.data.borrow();
+        data.index(x, y).map_or(0.0, |index| data.elevations[index])
+    }
+
+    /// Raises the tile up by `elevation`, e.g. to follow a terrain's height.
+    pub fn set_elevation(&mut self, x: i32, y: i32, elevation: f32) {
+        let mut data = self.data.borrow_mut();
+        let index = data.index(x, y).expect("Tile is outside of the layer");
+        data.elevations[index] = elevation;
+        data.cache = None;
+    }
+
+    pub fn fill(&mut self, tile: Option<u16>) {
+        let mut data = self.data.borrow_mut();
+        for t in &mut data.tiles {
+            *t = tile;
+        }
+        data.cache = None;
+    }
+
+    /// Sets all tiles from a row-by-row grid of terrain kinds.
+    ///
+    /// Tiles of the kinds without a rule are cleared.
+    pub fn autotile<K: Eq + Hash>(&mut self, terrain: &[K], rules: &HashMap<K, AutotileRule>) {
+        let mut data = self.data.borrow_mut();
+        assert_eq!(terrain.len(), data.tiles.len());
+        for y in 0..data.height {
+            for x in 0..data.width {
+                let index = data.index(x, y).expect("Bad tile index");
+                let kind = &terrain[index];
+                let is_border = |dx, dy| match data.index(x + dx, y + dy) {
+                    Some(neighbor) => terrain[neighbor] != *kind,
+                    None => false,
+                };
+                let tile = rules.get(kind).map(|rule| match *rule {
+                    AutotileRule::Fixed(tile) => tile,
+                    AutotileRule::Edges { first } => {
+                        let sides = [(0, -1, 1), (1, 0, 2), (0, 1, 4), (-1, 0, 8)];
+                        let mask = sides
+                            .iter()
+                            .filter(|&&(dx, dy, _)| is_border(dx, dy))
+                            .fold(0, |mask, &(_, _, bit)| mask | bit);
+                        first + mask
+                    }
+                });
+                data.tiles[index] = tile;
+            }
+        }
+        data.cache = None;
+    }
+
+    /// Returns the grid coordinates of the tile under the point,
+    /// ignoring the elevations.
+    pub fn tile_at(&self, point: Vec2) -> Option<(i32, i32)> {
+        let data = self.data.borrow();
+        let local = point - data.pos;
+        let (x, y) = match data.grid {
+            Grid::Square => (
+                (local.x / data.tile_size.x).floor(),
+                (local.y / data.tile_size.y).floor(),
+            ),
+            Grid::Isometric => {
+                let u = local.x / (data.tile_size.x * 0.5);
+                let v = local.y / (data.tile_size.y * 0.5);
+                (((u + v) * 0.5).floor(), ((v - u) * 0.5).floor())
+            }
+        };
+        let (x, y) = (x as i32, y as i32);
+        data.index(x, y).map(|_| (x, y))
+    }
+
+    pub fn is_same(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+
+    pub(crate) fn draw(&self) {
+        let mut data = self.data.borrow_mut();
+        let view = TileLayerData::view();
+        let is_outdated = match &data.cache {
+            Some(cache) => cache.view != view,
+            None => true,
+        };
+        let color = data.color;
+        if is_outdated {
+            let meshes = data.build_meshes(view);
+            data.cache = Some(Cache {
+                view,
+                color,
+                meshes,
+            });
+        }
+        let cache = data.cache.as_mut().expect("No tile meshes");
+        if cache.color != color {
+            for vertex in cache.meshes.iter_mut().flat_map(|mesh| &mut mesh.vertices) {
+                vertex.color = color;
+            }
+            cache.color = color;
+        }
+        for mesh in &cache.meshes {
+            models::draw_mesh(mesh);
+        }
+    }
+}